@@ -0,0 +1,490 @@
+use std::env;
+use origami::RoundPlan;
+use origami::Provenance;
+use origami::provenance::parse_provenance;
+use origami::index::PointIndexKind;
+use origami::index::parse_point_index;
+use origami::Tolerance;
+use origami::tolerance::make_tolerance;
+use origami::tolerance::MAX_TOLERANCE;
+use origami::make_round_plan;
+use origami::round::MAX_ROUNDS;
+use origami::plan::apply_preset;
+use origami::plan::preset_names;
+use origami::Polygon;
+use origami::draw::Outputs;
+use origami::draw::make_outputs;
+use origami::raster::MAX_PNG_SIZE;
+use origami::exact::EXACT_AXIOMS;
+use origami::style::Style;
+use origami::style::make_style;
+use origami::style::parse_curve;
+use origami::style::parse_color_by;
+use origami::style::parse_theme;
+use origami::polygon::make_unit_square;
+use origami::polygon::parse_polygon;
+
+// rounds are zero-indexed, matching the round argument of make_round
+const USAGE: &str = "usage: axiom-drawings [options]
+
+options:
+  -r, --rounds N               number of rounds to compute, up to 255 (default 2)
+  -a, --axioms LIST            axioms enabled in every round (default 1-7)
+      --round-axioms R=LIST    axioms enabled in round R, overrides --axioms
+  -i, --intersections LIST     rounds which compute intersection points (default 0-2)
+      --paper SHAPE            the convex paper folded: square, a4, triangle,
+                               hexagon, rectangle:RATIO (width / height) or
+                               regular:SIDES (default square). coordinates
+                               given or printed are in the shape's own units:
+                               a4 is 0.707 wide, the hexagon has radius 1
+                               around 0,0. the images fit it in a unit square
+      --seed FILE              more initial geometry, a text file of entries
+                               \"point X Y\" and \"line X1 Y1 X2 Y2\" (the
+                               crease through two points), # starts a comment
+      --save-state FILE        save the points and lines after every round
+      --resume FILE            continue from a saved state, up to --rounds.
+                               the paper, seed and provenance are the saved ones
+  -p, --preset NAME            input filter thresholds: light, points, points-full
+  -f, --filter R[:A]=P,L       in round R, axiom A (all axioms if omitted) only
+                               builds from points repeated at least P times
+                               and lines repeated at least L times
+      --provenance MODE        remember the parents of each point and line:
+                               none, first (construction) or all (default none)
+      --find-point X,Y         print the folds which make the point nearest X,Y
+      --find-line X,Y,X,Y      print the folds which make the line nearest the
+                               line through two points. finding turns on
+                               provenance (first) if it is none
+  -j, --threads N              threads used to build axioms (default 0, all cores)
+      --arithmetic KIND        float, or exact: count constructions with exact
+                               numbers a + b sqrt(r), a and b fractions, with
+                               no tolerance (default float). r fits the paper:
+                               3 for triangle and hexagon, otherwise 2. exact
+                               can't make axiom 6, leave it out with --axioms
+                               or --round-axioms. a fold of axioms 3 or 5
+                               whose square root isn't such a number stops the
+                               run. it can't seed, save, resume or find folds
+      --point-index KIND       how points are stored and searched for repeats:
+                               grid or quadtree (default grid). the results
+                               are the same, only the speed differs
+      --tolerance EPSILON      points and lines closer than this in every
+                               coordinate are the same, up to 0.01 (default
+                               1e-10, or with --resume the saved one, which
+                               this replaces). the merges near it are reported
+      --draw-rounds LIST       only draw points and lines first made in these
+                               rounds, 0 is the initial geometry (default all)
+      --color-by MODE          color points and lines: none, count, axiom
+                               or round (default none). axiom puts each
+                               axiom's lines in a layer, with a legend
+      --opacity CURVE          opacity of each point and line from its count:
+                               power:EXPONENT, log or linear (default power:0.1)
+      --theme THEME            dark (white on black) or light (default dark)
+      --size PX                the larger side of the images (default 907)
+      --stroke-width W         line width, the paper is 1 wide (default 0.0002)
+      --radius R               point radius (default 0.001)
+  -o, --out DIR                output directory (default images)
+      --points-file NAME       points svg file name (default points.svg)
+      --lines-file NAME        lines svg file name (default lines.svg)
+      --axiom-lines-file NAME  also write the lines of each axiom to their own
+                               svg, {} in NAME is the axiom, like lines-{}.svg
+      --png-file NAME          also draw the lines as a png, brighter where
+                               more lines (times their counts) pass
+      --png-size PX            the larger side of the png, up to 16384
+                               (default 2048)
+      --fold-file NAME         also write the drawn lines as a FOLD crease
+                               pattern in the paper's units, like creases.fold
+      --points-table NAME      also write the drawn points as a table: x, y,
+                               count, round. csv, or json if NAME ends in .json
+      --lines-table NAME       also write the drawn lines as a table: ux, uy, d,
+                               x1, y1, x2, y2 (the clipped segment), count,
+                               axiom, round. csv, or json if NAME ends in .json.
+                               the tables are in the paper's units
+      --exact-table NAME       with exact arithmetic, also write every point as
+                               a csv table of exact numbers: x, y, count, round
+  -h, --help                   print this message
+
+LIST is comma separated numbers and ranges, like 1,2,5-7. rounds start at 0.
+filters are applied after the preset, in the order given.
+";
+
+pub struct Config {
+	pub rounds: usize,
+	pub axioms: Vec<u8>,
+	pub round_axioms: Vec<(usize, Vec<u8>)>,
+	pub intersections: Vec<usize>,
+	pub paper: Polygon,
+	pub seed: Option<String>,
+	pub save_state: Option<String>,
+	pub resume: Option<String>,
+	pub preset: Option<String>,
+	// (round, axiom or 0 for all, min points, min lines)
+	pub filters: Vec<(usize, u8, u64, u64)>,
+	pub threads: usize,
+	pub exact: bool,
+	pub point_index: PointIndexKind,
+	// None if --tolerance wasn't given: a new run uses the default, a resumed
+	// run the saved one
+	pub tolerance: Option<Tolerance>,
+	pub provenance: Provenance,
+	pub find_points: Vec<(f64, f64)>,
+	pub find_lines: Vec<(f64, f64, f64, f64)>,
+	pub draw_rounds: Option<Vec<usize>>,
+	pub style: Style,
+	pub outputs: Outputs,
+}
+
+pub fn usage () -> &'static str { USAGE }
+
+impl Config {
+	pub fn default () -> Config {
+		Config {
+			rounds: 2,
+			axioms: vec![1, 2, 3, 4, 5, 6, 7],
+			round_axioms: vec![],
+			intersections: vec![0, 1, 2],
+			paper: make_unit_square(),
+			seed: None,
+			save_state: None,
+			resume: None,
+			preset: None,
+			filters: vec![],
+			threads: 0,
+			exact: false,
+			point_index: PointIndexKind::Grid,
+			tolerance: None,
+			provenance: Provenance::None,
+			find_points: vec![],
+			find_lines: vec![],
+			draw_rounds: None,
+			style: make_style(),
+			outputs: make_outputs(),
+		}
+	}
+	// the axioms, intersections and input filters of one round.
+	// the last --round-axioms for a round wins
+	pub fn plan (&self, round: usize) -> RoundPlan {
+		let mut plan = make_round_plan();
+		plan.axioms = match self.round_axioms.iter().rev().find(|el| el.0 == round) {
+			Some(el) => el.1.clone(),
+			None => self.axioms.clone(),
+		};
+		plan.intersections = self.intersections.contains(&round);
+		plan.threads = self.threads;
+		if let Some(ref name) = self.preset { apply_preset(&mut plan, name, round); }
+		for filter in self.filters.iter().filter(|el| el.0 == round) {
+			plan.set_filter(filter.1, filter.2, filter.3);
+		}
+		return plan;
+	}
+	pub fn draw_round (&self, round: u8) -> bool {
+		match self.draw_rounds {
+			Some(ref rounds) => rounds.contains(&(round as usize)),
+			None => true,
+		}
+	}
+}
+
+// "1,2,5-7" into [1, 2, 5, 6, 7]
+fn parse_list (string: &str) -> Result<Vec<usize>, String> {
+	let mut list: Vec<usize> = Vec::new();
+	if string.is_empty() { return Ok(list) }
+	for part in string.split(',') {
+		let range: Vec<&str> = part.splitn(2, '-').collect();
+		let start = parse_number(range[0])?;
+		let end = if range.len() == 2 { parse_number(range[1])? } else { start };
+		if end < start { return Err(format!("bad range \"{}\"", part)) }
+		for i in start..=end {
+			if !list.contains(&i) { list.push(i) }
+		}
+	}
+	return Ok(list);
+}
+
+fn parse_number (string: &str) -> Result<usize, String> {
+	string.trim().parse::<usize>()
+		.map_err(|_| format!("expected a number, found \"{}\"", string))
+}
+
+fn parse_axioms (string: &str) -> Result<Vec<u8>, String> {
+	let list = parse_list(string)?;
+	for i in 0..list.len() {
+		if list[i] < 1 || list[i] > 7 {
+			return Err(format!("axiom {} does not exist, expected 1 to 7", list[i]));
+		}
+	}
+	return Ok(list.iter().map(|el| *el as u8).collect());
+}
+
+// "R=LIST" into (R, LIST)
+fn parse_round_axioms (string: &str) -> Result<(usize, Vec<u8>), String> {
+	let pair: Vec<&str> = string.splitn(2, '=').collect();
+	if pair.len() != 2 { return Err(format!("expected ROUND=LIST, found \"{}\"", string)) }
+	return Ok((parse_number(pair[0])?, parse_axioms(pair[1])?));
+}
+
+// "R[:A]=P,L" into (R, A, P, L), A is 0 when omitted
+fn parse_filter (string: &str) -> Result<(usize, u8, u64, u64), String> {
+	let pair: Vec<&str> = string.splitn(2, '=').collect();
+	let key: Vec<&str> = pair[0].splitn(2, ':').collect();
+	let counts: Vec<&str> = if pair.len() == 2 { pair[1].split(',').collect() } else { vec![] };
+	if counts.len() != 2 { return Err(format!("expected R[:A]=P,L, found \"{}\"", string)) }
+	let round = parse_number(key[0])?;
+	let axiom = if key.len() == 2 { parse_axioms(key[1])? } else { vec![0] };
+	if axiom.len() != 1 { return Err(format!("expected one axiom, found \"{}\"", key[1])) }
+	let points = parse_number(counts[0])? as u64;
+	let lines = parse_number(counts[1])? as u64;
+	return Ok((round, axiom[0], points, lines));
+}
+
+fn parse_preset (string: &str) -> Result<String, String> {
+	if !preset_names().contains(&string) {
+		return Err(format!("unknown preset \"{}\"", string));
+	}
+	return Ok(string.to_string());
+}
+
+fn parse_positive (string: &str) -> Result<f64, String> {
+	match string.trim().parse::<f64>() {
+		Ok(number) if number > 0.0 => Ok(number),
+		_ => Err(format!("expected a positive number, found \"{}\"", string)),
+	}
+}
+
+fn parse_tolerance (string: &str) -> Result<Tolerance, String> {
+	let tolerance = string.trim().parse::<f64>().ok().and_then(make_tolerance);
+	return tolerance.ok_or(format!("expected a tolerance above 0, up to {}, found \"{}\"", MAX_TOLERANCE, string));
+}
+
+fn parse_floats (string: &str, count: usize) -> Result<Vec<f64>, String> {
+	let numbers: Vec<f64> = string.split(',')
+		.map(|el| el.trim().parse::<f64>())
+		.collect::<Result<Vec<f64>, _>>()
+		.map_err(|_| format!("expected numbers, found \"{}\"", string))?;
+	if numbers.len() != count {
+		return Err(format!("expected {} numbers, found \"{}\"", count, string));
+	}
+	return Ok(numbers);
+}
+
+// Ok(None) means the help message was requested
+pub fn parse (args: Vec<String>) -> Result<Option<Config>, String> {
+	let mut config = Config::default();
+	let mut i: usize = 0;
+	while i < args.len() {
+		let flag = args[i].as_str();
+		if flag == "-h" || flag == "--help" { return Ok(None) }
+		i += 1;
+		if i == args.len() { return Err(format!("missing value for {}", flag)) }
+		let value = args[i].as_str();
+		match flag {
+			"-r" | "--rounds" => config.rounds = parse_number(value)?,
+			"-a" | "--axioms" => config.axioms = parse_axioms(value)?,
+			"--round-axioms" => config.round_axioms.push(parse_round_axioms(value)?),
+			"-i" | "--intersections" => config.intersections = parse_list(value)?,
+			"--paper" => config.paper = parse_polygon(value)
+				.ok_or(format!("unknown or non-convex paper \"{}\"", value))?,
+			"--seed" => config.seed = Some(value.to_string()),
+			"--save-state" => config.save_state = Some(value.to_string()),
+			"--resume" => config.resume = Some(value.to_string()),
+			"-p" | "--preset" => config.preset = Some(parse_preset(value)?),
+			"-f" | "--filter" => config.filters.push(parse_filter(value)?),
+			"-j" | "--threads" => config.threads = parse_number(value)?,
+			"--arithmetic" => config.exact = match value {
+				"float" => false,
+				"exact" => true,
+				_ => return Err(format!("unknown arithmetic \"{}\"", value)),
+			},
+			"--point-index" => config.point_index = parse_point_index(value)
+				.ok_or(format!("unknown point index \"{}\"", value))?,
+			"--tolerance" => config.tolerance = Some(parse_tolerance(value)?),
+			"--provenance" => config.provenance = parse_provenance(value)
+				.ok_or(format!("unknown provenance mode \"{}\"", value))?,
+			"--find-point" => {
+				let n = parse_floats(value, 2)?;
+				config.find_points.push((n[0], n[1]));
+			}
+			"--find-line" => {
+				let n = parse_floats(value, 4)?;
+				config.find_lines.push((n[0], n[1], n[2], n[3]));
+			}
+			"--draw-rounds" => config.draw_rounds = Some(parse_list(value)?),
+			"--color-by" => config.style.color_by = parse_color_by(value)
+				.ok_or(format!("unknown color mode \"{}\"", value))?,
+			"--opacity" => config.style.curve = parse_curve(value)
+				.ok_or(format!("unknown opacity curve \"{}\"", value))?,
+			"--theme" => config.style.theme = parse_theme(value)
+				.ok_or(format!("unknown theme \"{}\"", value))?,
+			"--size" => config.style.size = parse_positive(value)?,
+			"--stroke-width" => config.style.stroke_width = parse_positive(value)?,
+			"--radius" => config.style.radius = parse_positive(value)?,
+			"-o" | "--out" => config.outputs.directory = value.to_string(),
+			"--points-file" => config.outputs.points_file = value.to_string(),
+			"--lines-file" => config.outputs.lines_file = value.to_string(),
+			"--axiom-lines-file" => {
+				if !value.contains("{}") { return Err(format!("expected {{}} in \"{}\"", value)) }
+				config.outputs.axiom_lines_file = Some(value.to_string());
+			}
+			"--png-file" => config.outputs.png_file = Some(value.to_string()),
+			"--png-size" => config.outputs.png_size = parse_number(value)?,
+			"--fold-file" => config.outputs.fold_file = Some(value.to_string()),
+			"--points-table" => config.outputs.points_table = Some(value.to_string()),
+			"--lines-table" => config.outputs.lines_table = Some(value.to_string()),
+			"--exact-table" => config.outputs.exact_table = Some(value.to_string()),
+			_ => return Err(format!("unknown option {}", flag)),
+		}
+		i += 1;
+	}
+	if config.rounds > MAX_ROUNDS {
+		return Err(format!("at most {} rounds can be computed, found {}", MAX_ROUNDS, config.rounds));
+	}
+	if config.outputs.png_size < 1 || config.outputs.png_size > MAX_PNG_SIZE {
+		return Err(format!("the png size is 1 to {} pixels, found {}", MAX_PNG_SIZE, config.outputs.png_size));
+	}
+	if config.resume.is_some() && config.seed.is_some() {
+		return Err("--seed can't be used with --resume, the saved state has its own".to_string());
+	}
+	let finding = config.find_points.len() + config.find_lines.len() > 0;
+	if config.exact && (config.seed.is_some() || config.resume.is_some() || config.save_state.is_some() || finding) {
+		return Err("exact arithmetic can't be used with --seed, --resume, --save-state or finding folds".to_string());
+	}
+	if config.exact && config.tolerance.is_some() {
+		return Err("exact arithmetic has no tolerance, --tolerance can't be used with it".to_string());
+	}
+	if config.exact {
+		for round in 0..config.rounds {
+			let plan = config.plan(round);
+			if let Some(axiom) = plan.axioms.iter().find(|axiom| !EXACT_AXIOMS.contains(axiom)) {
+				return Err(format!("exact arithmetic can't make axiom {} (its roots are a cubic's) in round {}, leave it out with --axioms or --round-axioms", axiom, round));
+			}
+		}
+	}
+	if !config.exact && config.outputs.exact_table.is_some() {
+		return Err("--exact-table needs --arithmetic exact".to_string());
+	}
+	if finding && config.provenance == Provenance::None {
+		config.provenance = Provenance::First;
+	}
+	return Ok(Some(config));
+}
+
+pub fn parse_env () -> Result<Option<Config>, String> {
+	parse(env::args().skip(1).collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn args (line: &str) -> Vec<String> {
+		return line.split_whitespace().map(|el| el.to_string()).collect();
+	}
+
+	fn config (line: &str) -> Config {
+		return parse(args(line)).unwrap().unwrap();
+	}
+
+	fn error (line: &str) -> String {
+		return parse(args(line)).err().unwrap();
+	}
+
+	#[test]
+	fn lists_and_ranges () {
+		assert_eq!(parse_list("1,2,5-7").unwrap(), vec![1, 2, 5, 6, 7]);
+		assert_eq!(parse_list("3-3,1, 3").unwrap(), vec![3, 1]);
+		assert_eq!(parse_list("").unwrap(), Vec::<usize>::new());
+		assert!(parse_list("7-5").is_err());
+		assert!(parse_list("1,,2").is_err());
+		assert!(parse_list("1-").is_err());
+		assert!(parse_list("a").is_err());
+		assert!(parse_list("-1").is_err());
+		assert_eq!(parse_axioms("7,1-2").unwrap(), vec![7, 1, 2]);
+		assert!(parse_axioms("0").is_err());
+		assert!(parse_axioms("6-8").is_err());
+	}
+
+	#[test]
+	fn filters () {
+		assert_eq!(parse_filter("2=12,8").unwrap(), (2, 0, 12, 8));
+		assert_eq!(parse_filter("3:5=72,64").unwrap(), (3, 5, 72, 64));
+		assert!(parse_filter("2=12").is_err());
+		assert!(parse_filter("2=12,8,4").is_err());
+		assert!(parse_filter("2").is_err());
+		assert!(parse_filter("2:8=1,1").is_err());
+		assert!(parse_filter("2:1-2=1,1").is_err());
+		assert!(parse_filter("x=1,1").is_err());
+	}
+
+	#[test]
+	fn defaults () {
+		let config = config("");
+		assert_eq!(config.rounds, 2);
+		assert_eq!(config.plan(0).axioms, vec![1, 2, 3, 4, 5, 6, 7]);
+		assert!(config.plan(2).intersections);
+		assert!(!config.plan(3).intersections);
+		assert!(config.tolerance.is_none());
+		assert_eq!(parse(args("--tolerance 1e-6")).unwrap().unwrap().tolerance.unwrap().epsilon, 1e-6);
+		assert!(parse(args("-r 3 --help")).unwrap().is_none());
+	}
+
+	// the last --round-axioms for a round wins, the others use --axioms.
+	// filters follow the preset, in the order given
+	#[test]
+	fn plans_by_round () {
+		let config = config("-j 3 -a 1,2 --round-axioms 1=3-4 --round-axioms 2=5 --round-axioms 1=7 -i 1 \
+			-p light -f 2=1,1 -f 2:6=9,9 -f 3:6=5,5");
+		assert_eq!(config.plan(0).axioms, vec![1, 2]);
+		assert_eq!(config.plan(1).axioms, vec![7]);
+		assert_eq!(config.plan(2).axioms, vec![5]);
+		assert_eq!(config.plan(3).axioms, vec![1, 2]);
+		assert!(!config.plan(0).intersections && config.plan(1).intersections);
+		assert_eq!(config.plan(3).threads, 3);
+		let plan = config.plan(2);
+		assert_eq!((plan.min_points(1), plan.min_lines(1)), (1, 1));
+		assert_eq!((plan.min_points(6), plan.min_lines(6)), (9, 9));
+		let plan = config.plan(3);
+		assert_eq!((plan.min_points(1), plan.min_lines(1)), (16, 6));
+		assert_eq!((plan.min_points(6), plan.min_lines(6)), (5, 5));
+	}
+
+	#[test]
+	fn bad_input () {
+		assert!(error("--rounds").starts_with("missing value"));
+		assert!(error("--rounds two").starts_with("expected a number"));
+		assert!(error("--bogus 1").starts_with("unknown option"));
+		assert!(error("-r 256").starts_with("at most 255 rounds"));
+		assert_eq!(config("-r 255").rounds, 255);
+		assert!(error("--paper circle").starts_with("unknown or non-convex paper"));
+		assert!(error("--arithmetic double").starts_with("unknown arithmetic"));
+		assert!(error("-p heavy").starts_with("unknown preset"));
+		assert!(error("--tolerance 0").starts_with("expected a tolerance"));
+		assert!(error("--tolerance 0.1").starts_with("expected a tolerance"));
+		assert!(error("--find-point 0.5").starts_with("expected 2 numbers"));
+		assert!(error("--size -1").starts_with("expected a positive number"));
+		assert!(error("--axiom-lines-file lines.svg").starts_with("expected {}"));
+		assert!(error("--png-size 0").starts_with("the png size is 1 to 16384"));
+		assert!(error("--png-size 16385").starts_with("the png size is 1 to 16384"));
+		assert_eq!(config("--png-size 16384").outputs.png_size, 16384);
+	}
+
+	#[test]
+	fn forbidden_combinations () {
+		assert!(error("--seed a.txt --resume b.bin").starts_with("--seed can't be used with --resume"));
+		for flags in ["--seed a.txt", "--resume b.bin", "--save-state b.bin", "--find-point 0.5,0.5"].iter() {
+			let line = format!("--arithmetic exact -a 1-5,7 {}", flags);
+			assert!(error(&line).starts_with("exact arithmetic can't be used"), "{}", line);
+		}
+		assert!(error("--arithmetic exact -a 1-5,7 --tolerance 1e-10").starts_with("exact arithmetic has no tolerance"));
+		assert!(error("--exact-table exact.csv").starts_with("--exact-table needs"));
+		assert!(config("--arithmetic exact -a 1-5,7 --exact-table exact.csv").exact);
+		// axiom 6 would be lost, in any round
+		assert!(error("--arithmetic exact").starts_with("exact arithmetic can't make axiom 6"));
+		assert!(error("--arithmetic exact -a 1-5,7 -r 2 --round-axioms 1=6").ends_with("in round 1, leave it out with --axioms or --round-axioms"));
+	}
+
+	// finding folds needs to remember how things were made
+	#[test]
+	fn finding_turns_on_provenance () {
+		assert_eq!(config("--find-line 0,0,1,1").provenance, Provenance::First);
+		assert_eq!(config("--provenance all --find-point 0.5,0.5").provenance, Provenance::All);
+		assert_eq!(config("").provenance, Provenance::None);
+	}
+}
@@ -0,0 +1,17 @@
+extern crate rabbit_ear;
+
+// the exploration of every crease the origami axioms can make, round by round.
+// the command line tool in main.rs is built on this.
+pub mod origami;
+
+// the geometry the exploration is built on
+pub mod math {
+	pub use rabbit_ear::Vector;
+	pub use rabbit_ear::Line;
+	pub use rabbit_ear::Segment;
+	pub use rabbit_ear::Rect;
+	pub use rabbit_ear::make_square;
+	pub use rabbit_ear::axioms;
+}
+
+pub use origami::*;
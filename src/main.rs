@@ -1,118 +1,210 @@
-extern crate rabbit_ear;
-use rabbit_ear as ear;
-use ear::Vector;
-use ear::Line;
-use ear::Segment;
-use ear::Rect;
-use ear::make_square;
-
-mod origami;
+extern crate origami_axioms;
+use std::path::Path;
+use origami_axioms::math::Vector;
+use origami_axioms::math::Line;
+use origami_axioms::math::Segment;
+use origami_axioms::math::axioms::axiom1;
+
+use origami_axioms::origami;
 use origami::GridVec;
-use origami::make_grid;
-// use QuadTree;
-// use make_tree;
+use origami::PointIndex;
+use origami::LineIndex;
+use origami::make_tracked_tree;
+use origami::index::PointIndexKind;
+use origami::index::copy_points;
 use origami::LineContainer;
-use origami::linecontainer::make_line_container;
-use origami::draw::draw;
-
-fn make_round (
-	round: usize,
-	point_quadtree: &mut GridVec,
-	line_container: &mut LineContainer,
-	boundary: Rect
-) {
-	// all axioms will be built from function arguments points and lines
-	// from the previous round (make points into Vector from the quadtree)
-	let points = point_quadtree.flatten();
-	let lines = line_container.flatten();
-	println!("round start {} points, {} lines", points.len(), lines.len());
-	// let points = point_quadtree.flatten_filter(match round {0=>0, 1=>0, 2=>0, 3=>4, _=>2});
-	// let lines = line_container.flatten_filter(match round {0=>0, 1=>0, 2=>0, 3=>0, _=>0});
-
-	// let points = point_quadtree.flatten_filter(match round {0..=1=>0, 2=>2, 3=>16, _=>144});
-	// let lines = line_container.flatten_filter(match round {0..=1=>0, 2=>0, 3=>6, _=>12});
-	// let pts_ax5 = point_quadtree.flatten_filter(match round {0..=1=>0, 2=>8, 3=>72, _=>144});
-	// let lns_ax5 = line_container.flatten_filter(match round {0..=1=>0, 2=>8, 3=>64, _=>144});
-	// let pts_ax6 = point_quadtree.flatten_filter(match round {0..=1=>0, 2=>12, 3=>72, _=>144});
-	// let lns_ax6 = line_container.flatten_filter(match round {0..=1=>0, 2=>12, 3=>64, _=>144});
-	// let pts_ax7 = point_quadtree.flatten_filter(match round {0..=1=>0, 2=>4, 3=>72, _=>144});
-	// let lns_ax7 = line_container.flatten_filter(match round {0..=1=>0, 2=>4, 3=>64, _=>144});
-
-	// // this is good for making points. faster than the other. half a million points.
-	// let points = point_quadtree.flatten_filter(match round {0..=1=>0, 2=>24, 3=>72, _=>144});
-	// let lines = line_container.flatten_filter(match round {0..=1=>0, 2=>16, 3=>64, _=>144});
-	// let pts_ax5 = point_quadtree.flatten_filter(match round {0..=1=>0, 2=>36, 3=>72, _=>144});
-	// let lns_ax5 = line_container.flatten_filter(match round {0..=1=>0, 2=>36, 3=>64, _=>144});
-	// let pts_ax6 = point_quadtree.flatten_filter(match round {0..=1=>0, 2=>52, 3=>72, _=>144});
-	// let lns_ax6 = line_container.flatten_filter(match round {0..=1=>0, 2=>52, 3=>64, _=>144});
-	// let pts_ax7 = point_quadtree.flatten_filter(match round {0..=1=>0, 2=>36, 3=>72, _=>144});
-	// let lns_ax7 = line_container.flatten_filter(match round {0..=1=>0, 2=>36, 3=>64, _=>144});
-
-	// // this is good for making points. still takes a while
-	// let points = point_quadtree.flatten_filter(match round {0..=1=>0, 2=>12, 3=>72, _=>144});
-	// let lines = line_container.flatten_filter(match round {0..=1=>0, 2=>8, 3=>64, _=>144});
-	// let pts_ax5 = point_quadtree.flatten_filter(match round {0..=1=>0, 2=>28, 3=>72, _=>144});
-	// let lns_ax5 = line_container.flatten_filter(match round {0..=1=>0, 2=>28, 3=>64, _=>144});
-	// let pts_ax6 = point_quadtree.flatten_filter(match round {0..=1=>0, 2=>32, 3=>72, _=>144});
-	// let lns_ax6 = line_container.flatten_filter(match round {0..=1=>0, 2=>32, 3=>64, _=>144});
-	// let pts_ax7 = point_quadtree.flatten_filter(match round {0..=1=>0, 2=>28, 3=>72, _=>144});
-	// let lns_ax7 = line_container.flatten_filter(match round {0..=1=>0, 2=>28, 3=>64, _=>144});
-
-	// new lines is all the lines made in THIS round
-	// let mut new_lines: Vec<(Line, u64)> = Vec::new();
-	let mut new_line_container: LineContainer = make_line_container();
-	// 1. compute all axioms for this round
-	origami::make_axiom1(&points, line_container, &mut new_line_container, boundary);
-	origami::make_axiom2(&points, line_container, &mut new_line_container, boundary);
-	origami::make_axiom3(&points, &lines, line_container, &mut new_line_container, boundary);
-	origami::make_axiom4(&points, &lines, line_container, &mut new_line_container, boundary);
-	origami::make_axiom5(&points, &lines, line_container, &mut new_line_container, boundary);
-	origami::make_axiom6(&points, &lines, line_container, &mut new_line_container, boundary);
-	origami::make_axiom7(&points, &lines, line_container, &mut new_line_container, boundary);
-	// origami::make_axiom5(&pts_ax5, &lns_ax5, line_container, &mut new_line_container, boundary);
-	// // origami::shortcut_axiom6(&points, &lines, line_container, &mut new_line_container, boundary);
-	// origami::make_axiom6(&pts_ax6, &lns_ax6, line_container, &mut new_line_container, boundary);
-	// origami::make_axiom7(&pts_ax7, &lns_ax7, line_container, &mut new_line_container, boundary);
-	// todo: list more axioms
-	// 2. compute new intersection points
-	// let mut new_points: Vec<(Vector, u64)> = if make_pts { origami::make_intersections(
-	// 	points, &mut new_lines) } else { Vec::new() };
-	// let mut new_points: Vec<(Vector, u64)> = origami::make_intersections(
-	// 	points, &mut new_lines);
-
-	let new_lines = new_line_container.flatten();
-	let old_lines = line_container.flatten();
-
-	// let mut new_points: GridVec = origami::make_intersections(
-	// 	point_quadtree, &old_lines, &new_lines, boundary);
-	let mut new_points: GridVec = if round < 3 {
-		origami::make_intersections(point_quadtree, &old_lines, &new_lines, boundary)
-	} else { make_grid() };
-
-	// point_quadtree, lines, &mut new_lines, boundary);
-	// 3. merge points and lines from this new round
-	point_quadtree.merge(&mut new_points);
-	line_container.merge(&mut new_line_container);
+use origami::draw;
+use origami::make_round;
+use origami::sequence;
+use origami::sequence::Step;
+use origami::CountLine;
+use origami::EdgeCount;
+use origami::Polygon;
+use origami::State;
+use origami::make_state;
+use origami::exact;
+
+mod cli;
+use cli::Config;
+
+// a step with its points and lines in the paper's units
+fn paper_step (step: &Step, paper: &Polygon) -> Step {
+	return Step {
+		points: step.points.iter().map(|p| paper.to_paper(*p)).collect(),
+		lines: step.lines.iter().map(|l| paper.line_to_paper(*l)).collect(),
+		point: step.point.map(|p| paper.to_paper(p)),
+		line: step.line.map(|l| paper.line_to_paper(l)),
+		axiom: step.axiom,
+		depth: step.depth,
+	};
+}
+
+fn print_steps (steps: Option<Vec<Step>>, paper: &Polygon) {
+	match steps {
+		Some(steps) => {
+			if steps.len() == 0 { println!("  part of the initial geometry"); }
+			for i in 0..steps.len() {
+				println!("  {}. {}", i + 1, sequence::format_step(&paper_step(&steps[i], paper)));
+			}
+		}
+		None => println!("  can't be made from the initial geometry"),
+	}
+}
+
+// for every --find-point and --find-line, the nearest point or line that was
+// made, and the folds that make it. the targets are given, and everything is
+// printed, in the paper's units
+fn print_sequences (config: &Config, points: &GridVec, lines: &LineContainer, paper: &Polygon) {
+	for &(x, y) in config.find_points.iter() {
+		let target = Vector { x, y };
+		let nearest = match sequence::nearest_point(points, &paper.from_paper(target)) {
+			Some(nearest) => nearest,
+			None => { println!("no points to search for {}", sequence::format_point(&target)); continue }
+		};
+		let found = paper.to_paper(nearest.0);
+		println!("nearest point to {} is {}, {} away, made {} times, first in round {}",
+			sequence::format_point(&target), sequence::format_point(&found),
+			sequence::point_distance(&target, &found), nearest.1, nearest.2);
+		print_steps(sequence::point_sequence(points, lines, &nearest.0), paper);
+	}
+	for &(x1, y1, x2, y2) in config.find_lines.iter() {
+		let (a, b) = (paper.from_paper(Vector { x: x1, y: y1 }), paper.from_paper(Vector { x: x2, y: y2 }));
+		let through = axiom1(a, b, paper.frame);
+		if through.len() == 0 {
+			println!("no line inside the boundary through ({}, {}) ({}, {})", x1, y1, x2, y2);
+			continue;
+		}
+		let nearest = match sequence::nearest_line(lines, &through[0]) {
+			Some(nearest) => nearest,
+			None => { println!("no lines to search for the line through ({}, {}) ({}, {})", x1, y1, x2, y2); continue }
+		};
+		let (target, found) = (paper.line_to_paper(through[0]), paper.line_to_paper(nearest.0));
+		println!("nearest line to {} is {}, {} away, made {} times, first in round {}",
+			sequence::format_line(&target), sequence::format_line(&found),
+			lines.tolerance().line_distance(&target, &found), nearest.1, nearest.3);
+		print_steps(sequence::line_sequence(points, lines, &nearest.0), paper);
+	}
+}
+
+// the paper's corners and sides, and the seed file if there is one
+fn initial_state (config: &Config) -> State {
+	let mut state: State = make_state(config.paper.clone(), config.provenance);
+	if let Some(ref path) = config.seed {
+		match origami::seed::read_seed(path, &state.paper) {
+			Ok(seed) => seed.apply(&state.paper, &mut state.points, &mut state.lines),
+			Err(message) => { eprintln!("error: {}", message); std::process::exit(2); }
+		}
+		println!("seeded {} points, {} lines", state.points.len(), state.lines.len());
+	}
+	return state;
+}
+
+// how many lookups merged, and how many were near the tolerance's edge
+fn print_edges (name: &str, edges: &EdgeCount) {
+	println!("{}: {} merged, {} of them from more than half the tolerance away, {} kept apart by less than twice it",
+		name, edges.merges, edges.close_merges, edges.close_misses);
+}
+
+// the rounds after the first "done", saving the state after each if asked
+fn make_rounds<P: PointIndex> (config: &Config, done: usize, paper: &Polygon, points: &mut P, lines: &mut LineContainer) {
+	for round in done..config.rounds {
+		make_round(round, &config.plan(round), points, lines, paper);
+		if let Some(ref path) = config.save_state {
+			if let Err(error) = origami::state::save_state(path, round + 1, paper, points, lines) {
+				eprintln!("error: can't save the state to {}: {}", path, error);
+				std::process::exit(1);
+			}
+		}
+	}
+	println!("tolerance {:e}", lines.tolerance().epsilon);
+	print_edges("points", &points.edges());
+	print_edges("lines", &lines.edges());
+}
+
+// every round in exact arithmetic, then the results as floats to draw
+fn make_exact_rounds (config: &Config) -> (GridVec, LineContainer) {
+	let mut state = match exact::make_exact_state(&config.paper) {
+		Some(state) => state,
+		None => {
+			eprintln!("error: exact arithmetic needs a paper whose corners are a + b sqrt(r) for small fractions a and b, like square, a4, triangle, hexagon or rectangle:RATIO");
+			std::process::exit(2);
+		}
+	};
+	println!("exact numbers are a + b sqrt({}), a and b fractions", state.r);
+	for round in 0..config.rounds {
+		if let Err(message) = exact::make_round(round, &config.plan(round), &mut state) {
+			eprintln!("error: exact round {}: {}", round, message);
+			std::process::exit(1);
+		}
+		println!("exact round {}: {} points, {} lines, largest denominator {}",
+			round, state.points_len(), state.lines_len(), state.largest_denominator());
+	}
+	if let Some(ref name) = config.outputs.exact_table {
+		let path = Path::new(&config.outputs.directory).join(name);
+		let table = match state.points_csv() {
+			Some(table) => table,
+			None => {
+				eprintln!("error: the exact points are too large to sort for the table");
+				std::process::exit(1);
+			}
+		};
+		if let Err(error) = origami::draw::write(&path, table.as_bytes()) {
+			eprintln!("error: {}", error);
+			std::process::exit(1);
+		}
+	}
+	return state.to_containers();
 }
 
 fn main () {
-	// the boundary, all points and lines will be clipped inside
-	let unit_square: Rect = make_square();
-
-	// the initial geometry from which all folds will be made
-	let mut points: GridVec = make_grid();
-	let mut lines: LineContainer = make_line_container();	
-	points.push(Vector { x: 0.0, y: 0.0 });
-	points.push(Vector { x: 1.0, y: 0.0 });
-	points.push(Vector { x: 1.0, y: 1.0 });
-	points.push(Vector { x: 0.0, y: 1.0 });
-	unit_square.sides.iter().for_each(|side| lines.push(side, 0));
-
-	for round in 0..2 {
-		make_round(round, &mut points, &mut lines, unit_square);
-		// println!("done round {} ({} lines {} points)", round + 1, lines.len(), points.len());
-		// 	because some lines are being made outside of the square, we need to filter
-		// 	out lines based on if they become segments.
+	let config: Config = match cli::parse_env() {
+		Ok(Some(config)) => config,
+		Ok(None) => { print!("{}", cli::usage()); return }
+		Err(message) => {
+			eprintln!("error: {}\n\n{}", message, cli::usage());
+			std::process::exit(2);
+		}
+	};
+
+	let mut state: State = match config.resume {
+		Some(ref path) => match origami::state::load_state(path) {
+			Ok(state) => state,
+			Err(error) => { eprintln!("error: can't resume from {}: {}", path, error); std::process::exit(2); }
+		},
+		None => initial_state(&config),
+	};
+	if config.resume.is_some() {
+		println!("resumed after round {}, {} points, {} lines",
+			state.rounds, state.points.len(), state.lines.len());
+		if state.points.provenance != config.provenance {
+			println!("the saved state keeps provenance {:?}", state.points.provenance);
+		}
+	}
+	// a resumed run keeps the saved tolerance unless --tolerance is given
+	if let Some(tolerance) = config.tolerance {
+		let old = state.set_tolerance(tolerance);
+		if let (Some(old), true) = (old, config.resume.is_some()) {
+			println!("the state was saved with tolerance {:e}, its points and lines are refiled for {:e}, {} points, {} lines",
+				old.epsilon, tolerance.epsilon, state.points.len(), state.lines.len());
+		}
+	}
+	let State { rounds: done, paper, mut points, mut lines } = state;
+	// the paper, all points and lines will be clipped inside
+	let paper: &Polygon = &paper;
+
+	if config.exact {
+		let (exact_points, exact_lines) = make_exact_rounds(&config);
+		points = exact_points;
+		lines = exact_lines;
+	} else if config.point_index == PointIndexKind::Grid {
+		make_rounds(&config, done, paper, &mut points, &mut lines);
+	} else {
+		let mut tree = make_tracked_tree(points.provenance);
+		tree.set_tolerance(points.tolerance);
+		copy_points(&points, &mut tree);
+		make_rounds(&config, done, paper, &mut tree, &mut lines);
+		points = points.make_empty();
+		copy_points(&tree, &mut points);
 	}
 
 	let flat_lines = lines.flatten();
@@ -120,48 +212,27 @@ fn main () {
 
 	println!("finished, {} lines, {} points", flat_lines.len(), flat_points.len());
 
+	print_sequences(&config, &points, &lines, paper);
+
 	// temporarily put a tuple in a tuple
 	// (line, number_of_repeats, (clipping_success, segment))
-	let mut segments: Vec<(Segment, u64)> = flat_lines.iter()
-		.map(|el: &(Line, u64, u8)| (el.0, el.1, unit_square.clip(el.0)))
-		.filter(|el: &(Line, u64, (bool, Segment))| (el.2).0)
-		.map(|el| ( (el.2).1, el.1) )
+	let mut segments: Vec<(Segment, u64, u8, u8)> = flat_lines.iter()
+		.filter(|el: &&(Line, u64, u8, u8)| config.draw_round(el.3))
+		.map(|el: &(Line, u64, u8, u8)| (el.0, el.1, el.2, el.3, paper.clip(el.0)))
+		.filter(|el: &(Line, u64, u8, u8, (bool, Segment))| (el.4).0)
+		.map(|el| ( (el.4).1, el.1, el.2, el.3) )
+		.collect();
+	let mut marks: Vec<(Vector, u64, u8)> = flat_points.into_iter()
+		.filter(|el| config.draw_round(el.2))
 		.collect();
-	let mut marks: Vec<(Vector, u64)> = flat_points;
 	segments.sort_by_key(|el| el.1);
 	marks.sort_by_key(|el| el.1);
+	let drawn_lines: Vec<CountLine> = flat_lines.into_iter()
+		.filter(|el| config.draw_round(el.3))
+		.collect();
 
-	draw(&segments, &marks);
-
-	// for i in 0..segments.len() {
-	//     println!("{}: {:?}", i, segments[i]);
-	// }
-
-	// println!("finished. {} lines, {} segments, {} points",
-	// 	lines.len(), segments.len(), points.len());
-
-
-	// // testing for quadtree
-	// let mut tree = make_grid();
-	// println!("tree before {} {}", tree.quadrants.len(), tree.points.len());
-	// tree.push(Vector { x: 0.5, y: 0.25 });
-	// tree.push(Vector { x: 1.0, y: 0.15 });
-	// tree.push(Vector { x: 0.5, y: 0.5 });
-	// tree.push(Vector { x: 0.5, y: 0.75 });
-	// tree.push(Vector { x: 0.75, y: 0.85 });
-	// tree.push(Vector { x: 1.0, y: 0.15 });
-	// println!("tree after {} {}", tree.quadrants.len(), tree.points.len());
-	// for i in 0..tree.quadrants.len() {
-	// 	println!("-- {} inside ({}) {}", i, tree.quadrants[i].quadrants.len(), tree.quadrants[i].points.len());
-	// }
-	// let points = tree.flatten();
-	// println!("all points, {}", points.len());
-	// for i in 0..points.len() {
-	// 	println!("{}: {:?}", i, points[i]);
-	// }
-
-	// println!("tree {:?}", tree);
-	// for i in 0..tree.quadrants.len() {
-	// 	println!("tree {} {:?}", i, tree.quadrants[i]);
-	// }
+	if let Err(error) = draw(&segments, &marks, &drawn_lines, paper, &config.outputs, &config.style, &lines.tolerance()) {
+		eprintln!("error: {}", error);
+		std::process::exit(1);
+	}
 }
@@ -3,17 +3,34 @@ use rabbit_ear::Line;
 use rabbit_ear::Segment;
 
 pub mod gridvec;
-// pub mod quadtree;
+pub mod quadtree;
+pub mod index;
 pub mod linecontainer;
 pub mod make;
+pub mod round;
 pub mod draw;
+pub mod style;
+pub mod raster;
+pub mod fold;
+pub mod table;
+pub mod plan;
+pub mod provenance;
+pub mod sequence;
+pub mod polygon;
+pub mod seed;
+pub mod state;
+pub mod exact;
+pub mod tolerance;
 
 // tuple pairs for point and line with a second parameter
-// which keeps track of how many repeated occurences there are
-pub type CountPoint = (Vector, u64);
-pub type CountLine = (Line, u64, u8);
-pub type CountSegment = (Segment, u64);
+// which keeps track of how many repeated occurences there are.
+// lines and segments also keep the axiom which first made them, and all
+// keep the round they first appeared in (0 is the initial geometry)
+pub type CountPoint = (Vector, u64, u8);
+pub type CountLine = (Line, u64, u8, u8);
+pub type CountSegment = (Segment, u64, u8, u8);
 
+pub use self::round::make_round;
 pub use self::make::make_intersections;
 pub use self::make::make_axiom1;
 pub use self::make::make_axiom2;
@@ -23,8 +40,31 @@ pub use self::make::make_axiom5;
 pub use self::make::make_axiom6;
 pub use self::make::make_axiom7;
 
-// pub use self::quadtree::QuadTree;
-// pub use self::quadtree::make_tree;
+pub use self::quadtree::QuadTree;
+pub use self::quadtree::make_tree;
+pub use self::quadtree::make_tracked_tree;
+pub use self::index::PointIndex;
+pub use self::index::LineIndex;
 pub use self::gridvec::GridVec;
 pub use self::gridvec::make_grid;
+pub use self::gridvec::make_tracked_grid;
 pub use self::linecontainer::LineContainer;
+pub use self::linecontainer::make_line_container;
+pub use self::linecontainer::make_tracked_line_container;
+pub use self::provenance::Origin;
+pub use self::provenance::Provenance;
+pub use self::tolerance::Tolerance;
+pub use self::tolerance::EdgeCount;
+pub use self::polygon::Polygon;
+pub use self::plan::RoundPlan;
+pub use self::plan::make_round_plan;
+pub use self::state::State;
+pub use self::state::make_state;
+pub use self::draw::draw;
+pub use self::draw::svg_lines;
+pub use self::draw::svg_points;
+pub use self::draw::Outputs;
+pub use self::draw::make_outputs;
+pub use self::draw::DrawError;
+pub use self::style::Style;
+pub use self::style::make_style;
@@ -1,5 +1,9 @@
+use std::fs;
 use std::fs::File;
-// use std::fs;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
 use std::io::prelude::*;
 use rabbit_ear as ear;
 use self::ear::Vector;
@@ -7,44 +11,71 @@ use self::ear::Segment;
 use origami::CountPoint;
 use origami::CountLine;
 use origami::CountSegment;
+use origami::Polygon;
+use origami::Tolerance;
+use origami::fold::fold_json;
+use origami::table;
+use origami::style::Style;
+use origami::style::ColorBy;
+use origami::raster::make_raster;
 
-const STROKE_W: f64 = 0.0002;
-const RADIUS: f64 = 0.001;
+const MARGIN: f64 = 0.01;
+// the legend is to the right of the paper
+const LEGEND_W: f64 = 0.3;
+const LEGEND_ROW: f64 = 0.04;
 
-const SVG_HEADER: &str= "<svg version=\"1.1\" xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"-0.01 -0.01 1.02 1.02\" width=\"907px\" height=\"907px\">\n";
+// (x, y, w, h), the paper with a small margin, and the legend if there is one
+fn view_box (paper: &Polygon, legend: bool) -> (f64, f64, f64, f64) {
+	let (min, max) = paper.bounds();
+	let legend_w = if legend { LEGEND_W } else { 0.0 };
+	(min.x - MARGIN, min.y - MARGIN, max.x - min.x + 2.0 * MARGIN + legend_w, max.y - min.y + 2.0 * MARGIN)
+}
+
+fn svg_header (paper: &Polygon, style: &Style, legend: bool) -> String {
+	let (x, y, w, h) = view_box(paper, legend);
+	let scale = style.size / w.max(h);
+	format!("<svg version=\"1.1\" xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}px\" height=\"{}px\">\n",
+		x, y, w, h, (w * scale).round(), (h * scale).round())
+}
 
-fn unit_square_boundary() -> String {
-	format!("<g stroke=\"white\" stroke-width=\"{}\" stroke-opacity=\"1.0\">\n<line x1=\"0\" y1=\"0\" x2=\"1\" y2=\"0\" />\n<line x1=\"1\" y1=\"0\" x2=\"1\" y2=\"1\" />\n<line x1=\"1\" y1=\"1\" x2=\"0\" y2=\"1\" />\n<line x1=\"0\" y1=\"1\" x2=\"0\" y2=\"0\" />\n</g>\n", STROKE_W)
+fn paper_boundary (paper: &Polygon, style: &Style) -> String {
+	let mut string = format!("<g stroke=\"{}\" stroke-width=\"{}\" stroke-opacity=\"1.0\">\n",
+		style.foreground(), style.stroke_width);
+	let n = paper.vertices.len();
+	for i in 0..n {
+		let (a, b) = (paper.vertices[i], paper.vertices[(i + 1) % n]);
+		string.push_str(&format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" />\n", a.x, a.y, b.x, b.y));
+	}
+	string.push_str("</g>\n");
+	return string;
 }
 
-fn scale_float (opacity: f64) -> f64 {
-	opacity.powf(0.1)
-	// opacity.powf(0.333)
-	// opacity.powf(0.333) * 0.666 + 0.333
-	// let opacity: f64 = pct.powf(0.4) * 0.8;
-	// let opacity: f64 = pct.powf(0.6) * 0.75;
-	// let opacity: f64 = 0.03 + 0.15 * (pct.powf(0.3);
-	// let opacity: f64 = pct.powf(0.75);
+// fills the whole view box
+fn background (paper: &Polygon, style: &Style, legend: bool) -> String {
+	let (x, y, w, h) = view_box(paper, legend);
+	format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"none\" />\n",
+		x, y, w, h, style.background())
 }
 
-fn circle_elements (points: &Vec<CountPoint>) -> String {
+fn circle_elements (points: &Vec<CountPoint>, style: &Style) -> String {
 	let mut strings: Vec<String> = Vec::new();
 	// get the largest repeat value. scale all others in relation to this
 	let mut repeat_max_u64: u64 = 0;
 	for i in 0..points.len() {
 		if points[i].1 > repeat_max_u64 { repeat_max_u64 = points[i].1; }
 	}
-	let repeat_max: f64 = repeat_max_u64 as f64;
-	println!("one point appears {} times. lowest opacity: {}", repeat_max_u64, scale_float(1.0/repeat_max));
+	println!("one point appears {} times. lowest opacity: {}", repeat_max_u64, style.opacity(1, repeat_max_u64));
 	for i in 0..points.len() {
-		let pct: f64 = (points[i].1 as f64) / repeat_max; // (0.0, 1.0]
-		let opacity: f64 = scale_float(pct);
+		let opacity: f64 = style.opacity(points[i].1, repeat_max_u64);
 		let mut string: String = String::new();
 		string.push_str("<circle ");
 		string.push_str(&format!("cx=\"{}\" ", points[i].0.x));
 		string.push_str(&format!("cy=\"{}\" ", points[i].0.y));
-		string.push_str(&format!("r=\"{}\" ", RADIUS));
+		string.push_str(&format!("r=\"{}\" ", style.radius));
 		string.push_str(&format!("opacity=\"{}\" ", opacity));
+		if let Some(color) = style.color(points[i].1, repeat_max_u64, 0, points[i].2) {
+			string.push_str(&format!("fill=\"{}\" ", color));
+		}
 		string.push_str("/>\n");
 		strings.push(string);
 	}
@@ -55,31 +86,23 @@ fn circle_elements (points: &Vec<CountPoint>) -> String {
 	return string;
 }
 
-fn line_elements (segments: &Vec<CountSegment>) -> String {
+// opacities are relative to repeat_max, the largest count in the whole image
+fn line_elements (segments: &Vec<CountSegment>, repeat_max_u64: u64, style: &Style) -> String {
 	let mut strings: Vec<String> = Vec::new();
-	// get the largest repeat value. scale all others in relation to this
-	let mut repeat_max_u64: u64 = 0;
 	for i in 0..segments.len() {
-		if segments[i].1 > repeat_max_u64 { repeat_max_u64 = segments[i].1; }
-	}
-	let repeat_max: f64 = repeat_max_u64 as f64;
-	println!("one line appears {} times. lowest opacity: {}", repeat_max_u64, scale_float(1.0/repeat_max));
-	for i in 0..segments.len() {
-		let pct: f64 = (segments[i].1 as f64) / repeat_max; // (0.0, 1.0]
-		// let pct2: f64 = ((segments[i].1 - 1) as f64) / repeat_max;  // [0.0, 1.0)
-		// let gray: u8 = (255.0 * pct.powf(0.33)).floor() as u8;
-		// let hue: u8 = (pct2 * 300.0) as u8;
-		let opacity: f64 = scale_float(pct);
+		let opacity: f64 = style.opacity(segments[i].1, repeat_max_u64);
 		let mut string: String = String::new();
 		string.push_str("<line ");
 		string.push_str(&format!("count=\"{}\" ", segments[i].1));
+		string.push_str(&format!("round=\"{}\" ", segments[i].3));
 		string.push_str(&format!("x1=\"{:.8}\" ", segments[i].0.a.x));
 		string.push_str(&format!("y1=\"{:.8}\" ", segments[i].0.a.y));
 		string.push_str(&format!("x2=\"{:.8}\" ", segments[i].0.b.x));
 		string.push_str(&format!("y2=\"{:.8}\" ", segments[i].0.b.y));
 		string.push_str(&format!("stroke-opacity=\"{:.4}\" ", opacity));
-		// string.push_str(&format!("stroke=\"rgb({},{},{})\" ", gray, gray, gray));
-		// string.push_str(&format!("stroke=\"hsl({}, 85%, 45%)\" ", hue));
+		if let Some(color) = style.color(segments[i].1, repeat_max_u64, segments[i].2, segments[i].3) {
+			string.push_str(&format!("stroke=\"{}\" ", color));
+		}
 		string.push_str("/>\n");
 		strings.push(string);
 	}
@@ -90,39 +113,186 @@ fn line_elements (segments: &Vec<CountSegment>) -> String {
 	return string;
 }
 
-pub fn svg_lines(segments: &Vec<CountSegment>) -> String {
+// the axioms which made these lines, in order
+fn axioms_of (segments: &Vec<CountSegment>) -> Vec<u8> {
+	let mut axioms: Vec<u8> = segments.iter().map(|el| el.2).collect();
+	axioms.sort();
+	axioms.dedup();
+	return axioms;
+}
+
+// one color swatch and name for each axiom layer, next to the paper
+fn legend (segments: &Vec<CountSegment>, axioms: &Vec<u8>, paper: &Polygon, style: &Style) -> String {
+	let (min, max) = paper.bounds();
+	let x = max.x + 2.0 * MARGIN;
+	let mut string = format!("<g id=\"legend\" font-family=\"sans-serif\" font-size=\"{}\" fill=\"{}\">\n",
+		LEGEND_ROW * 0.6, style.foreground());
+	for i in 0..axioms.len() {
+		let y = min.y + LEGEND_ROW * (i as f64 + 0.5);
+		let count = segments.iter().filter(|el| el.2 == axioms[i]).count();
+		let name = if axioms[i] == 0 { "paper".to_string() } else { format!("axiom {}", axioms[i]) };
+		string.push_str(&format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\" />\n",
+			x, y, x + LEGEND_ROW, y, style.axiom_color(axioms[i]), LEGEND_ROW * 0.2));
+		string.push_str(&format!("<text x=\"{}\" y=\"{}\" dominant-baseline=\"middle\">{} ({} lines)</text>\n",
+			x + LEGEND_ROW * 1.5, y, name, count));
+	}
+	string.push_str("</g>\n");
+	return string;
+}
+
+// when coloring by axiom, the lines are in one layer (group) for each axiom,
+// with a legend
+pub fn svg_lines(segments: &Vec<CountSegment>, paper: &Polygon, style: &Style) -> String {
+	// get the largest repeat value. scale all others in relation to this
+	let mut repeat_max_u64: u64 = 0;
+	for i in 0..segments.len() {
+		if segments[i].1 > repeat_max_u64 { repeat_max_u64 = segments[i].1; }
+	}
+	println!("one line appears {} times. lowest opacity: {}", repeat_max_u64, style.opacity(1, repeat_max_u64));
+	let layered = style.color_by == ColorBy::Axiom;
 	let mut svg: String = String::new();
-	svg.push_str(&SVG_HEADER.to_string());
-	svg.push_str("<rect x=\"-1\" y=\"-1\" width=\"3\" height=\"3\" fill=\"black\" stroke=\"none\" />\n");
-	svg.push_str(&format!("<g fill=\"none\" stroke=\"white\" stroke-width=\"{}\">\n", STROKE_W));
-	svg.push_str(&line_elements(&segments));
-	svg.push_str("</g>\n");
-	svg.push_str(&unit_square_boundary());
+	svg.push_str(&svg_header(paper, style, layered));
+	svg.push_str(&background(paper, style, layered));
+	if layered {
+		// the layer sets the color, not each line
+		let plain = Style { color_by: ColorBy::None, ..*style };
+		let axioms = axioms_of(segments);
+		for axiom in axioms.iter() {
+			let layer: Vec<CountSegment> = segments.iter().filter(|el| el.2 == *axiom).cloned().collect();
+			svg.push_str(&format!("<g id=\"axiom-{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\">\n",
+				axiom, style.axiom_color(*axiom), style.stroke_width));
+			svg.push_str(&line_elements(&layer, repeat_max_u64, &plain));
+			svg.push_str("</g>\n");
+		}
+		svg.push_str(&legend(segments, &axioms, paper, style));
+	} else {
+		svg.push_str(&format!("<g fill=\"none\" stroke=\"{}\" stroke-width=\"{}\">\n",
+			style.foreground(), style.stroke_width));
+		svg.push_str(&line_elements(&segments, repeat_max_u64, style));
+		svg.push_str("</g>\n");
+	}
+	svg.push_str(&paper_boundary(paper, style));
 	svg.push_str("</svg>\n");
 	return svg;
 }
 
-pub fn svg_points(points: &Vec<CountPoint>) -> String {
+// only the lines first made by one axiom, colored and with a legend like
+// svg_lines when coloring by axiom. the opacities are relative to these lines
+pub fn svg_axiom_lines(segments: &Vec<CountSegment>, axiom: u8, paper: &Polygon, style: &Style) -> String {
+	let layer: Vec<CountSegment> = segments.iter().filter(|el| el.2 == axiom).cloned().collect();
+	return svg_lines(&layer, paper, &Style { color_by: ColorBy::Axiom, ..*style });
+}
+
+pub fn svg_points(points: &Vec<CountPoint>, paper: &Polygon, style: &Style) -> String {
 	let mut svg: String = String::new();
-	svg.push_str(&SVG_HEADER.to_string());
-	svg.push_str("<rect x=\"-1\" y=\"-1\" width=\"3\" height=\"3\" fill=\"black\" stroke=\"none\" />\n");
-	svg.push_str("<g fill=\"white\" stroke=\"none\">\n");
-	svg.push_str(&circle_elements(&points));
+	svg.push_str(&svg_header(paper, style, false));
+	svg.push_str(&background(paper, style, false));
+	svg.push_str(&format!("<g fill=\"{}\" stroke=\"none\">\n", style.foreground()));
+	svg.push_str(&circle_elements(&points, style));
 	svg.push_str("</g>\n");
-	// svg.push_str(&unit_square_boundary());
+	// svg.push_str(&paper_boundary(paper, style));
 	svg.push_str("</svg>\n");
 	return svg;
 }
 
-fn write(filename: String, data: &String) -> std::io::Result<()> {
-	let mut file = File::create(format!("images/{}", filename))?;
-	file.write_all(data.as_bytes())?;
+// where draw writes. the files are inside the directory, a file which is
+// None isn't written
+pub struct Outputs {
+	pub directory: String,
+	pub points_file: String,
+	pub lines_file: String,
+	pub fold_file: Option<String>,
+	// one svg of lines for each axiom, "{}" in the name is the axiom
+	pub axiom_lines_file: Option<String>,
+	// csv, or json if the name ends in .json
+	pub points_table: Option<String>,
+	pub lines_table: Option<String>,
+	// with exact arithmetic, the points as fractions. main writes it, draw
+	// only has the floats
+	pub exact_table: Option<String>,
+	// a density image of the lines, and its larger side in pixels
+	pub png_file: Option<String>,
+	pub png_size: usize,
+}
+
+pub fn make_outputs () -> Outputs {
+	Outputs {
+		directory: "images".to_string(),
+		points_file: "points.svg".to_string(),
+		lines_file: "lines.svg".to_string(),
+		fold_file: None,
+		axiom_lines_file: None,
+		points_table: None,
+		lines_table: None,
+		exact_table: None,
+		png_file: None,
+		png_size: 2048,
+	}
+}
+
+// the directory or file draw couldn't make, and why
+#[derive(Debug)]
+pub enum DrawError {
+	Directory(PathBuf, io::Error),
+	File(PathBuf, io::Error),
+}
+
+impl fmt::Display for DrawError {
+	fn fmt (&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			DrawError::Directory(ref path, ref error) =>
+				write!(f, "can't create the directory {}: {}", path.display(), error),
+			DrawError::File(ref path, ref error) =>
+				write!(f, "can't write {}: {}", path.display(), error),
+		}
+	}
+}
+
+impl std::error::Error for DrawError {}
+
+// the file's directories are made if they don't exist
+pub fn write (path: &Path, data: &[u8]) -> Result<(), DrawError> {
+	if let Some(directory) = path.parent() {
+		fs::create_dir_all(directory).map_err(|error| DrawError::Directory(directory.to_path_buf(), error))?;
+	}
+	let mut file = File::create(path).map_err(|error| DrawError::File(path.to_path_buf(), error))?;
+	file.write_all(data).map_err(|error| DrawError::File(path.to_path_buf(), error))?;
 	Ok(())
 }
 
-pub fn draw (segments: &Vec<CountSegment>, points: &Vec<CountPoint>) {
+pub fn draw (
+	segments: &Vec<CountSegment>,
+	points: &Vec<CountPoint>,
+	lines: &Vec<CountLine>, // the lines of the segments, for the lines table
+	paper: &Polygon,
+	outputs: &Outputs,
+	style: &Style,
+	tolerance: &Tolerance // points closer than this are one vertex of the FOLD file
+) -> Result<(), DrawError> {
 	println!("DRAW");
-	// fs::create_dir_all("/images")?;
-	let _res_p = write("points.svg".to_string(), &svg_points(points));
-	let _res_l = write("lines.svg".to_string(), &svg_lines(segments));
+	let directory = Path::new(&outputs.directory);
+	write(&directory.join(&outputs.points_file), svg_points(points, paper, style).as_bytes())?;
+	write(&directory.join(&outputs.lines_file), svg_lines(segments, paper, style).as_bytes())?;
+	if let Some(ref axiom_lines_file) = outputs.axiom_lines_file {
+		for axiom in axioms_of(segments).iter() {
+			let name = axiom_lines_file.replace("{}", &axiom.to_string());
+			write(&directory.join(name), svg_axiom_lines(segments, *axiom, paper, style).as_bytes())?;
+		}
+	}
+	if let Some(ref png_file) = outputs.png_file {
+		let raster = make_raster(segments, paper, outputs.png_size);
+		write(&directory.join(png_file), &raster.png(style))?;
+	}
+	if let Some(ref fold_file) = outputs.fold_file {
+		write(&directory.join(fold_file), fold_json(segments, points, paper, tolerance).as_bytes())?;
+	}
+	if let Some(ref points_table) = outputs.points_table {
+		let data = if table::is_json(points_table) { table::points_json(points, paper) } else { table::points_csv(points, paper) };
+		write(&directory.join(points_table), data.as_bytes())?;
+	}
+	if let Some(ref lines_table) = outputs.lines_table {
+		let data = if table::is_json(lines_table) { table::lines_json(lines, paper) } else { table::lines_csv(lines, paper) };
+		write(&directory.join(lines_table), data.as_bytes())?;
+	}
+	return Ok(());
 }
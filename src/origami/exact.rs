@@ -0,0 +1,788 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use rabbit_ear as ear;
+use self::ear::Vector;
+use self::ear::Line;
+use origami::CountPoint;
+use origami::CountLine;
+use origami::GridVec;
+use origami::make_grid;
+use origami::LineContainer;
+use origami::make_line_container;
+use origami::PointIndex;
+use origami::LineIndex;
+use origami::Polygon;
+use origami::RoundPlan;
+use origami::round::MAX_ROUNDS;
+
+// exact arithmetic, for counting constructions without a tolerance.
+// every number is a + b sqrt(r), with a and b fractions and r one square free
+// integer for the whole exploration, picked to fit the paper's corners: 3 for
+// the triangle and hexagon, 2 for a4 and for papers with fractional corners.
+// these numbers are a field, so the crossing of two lines and axioms 1, 2, 4
+// and 7 always stay inside it. axioms 3 and 5 also take a square root (of a
+// line's normal, and of where a circle crosses a line), which is only made
+// when the root is in the field too. two constructions are the same exactly
+// when their numbers are equal.
+// axiom 6 solves a cubic, its roots are in a tower of field extensions which
+// isn't represented here, so exact mode can't make it and the command line
+// refuses it. a square root which leaves the field, or numbers which don't
+// fit in fractions of i128, would lose constructions, so they stop the round
+// with an error instead of undercounting
+
+// the axioms exact mode can make
+pub const EXACT_AXIOMS: [u8; 6] = [1, 2, 3, 4, 5, 7];
+
+// the r tried for a paper's corners, in order. a paper with fractional
+// corners gets 2, which bisecting a diagonal and a side needs
+const RADICANDS: [i128; 10] = [2, 3, 5, 6, 7, 10, 11, 13, 14, 15];
+
+fn gcd (a: i128, b: i128) -> i128 {
+	let (mut a, mut b) = (a.abs(), b.abs());
+	while b != 0 { let t = a % b; a = b; b = t; }
+	return a;
+}
+
+// the root of a perfect square
+fn integer_sqrt (n: i128) -> Option<i128> {
+	if n < 0 { return None }
+	// the root is below 2^64, so the squares near it fit in a u128
+	let n = n as u128;
+	let mut root = (n as f64).sqrt() as u128;
+	while root * root > n { root -= 1; }
+	while (root + 1) * (root + 1) <= n { root += 1; }
+	return if root * root == n { Some(root as i128) } else { None };
+}
+
+// always reduced, the denominator is positive
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Rational {
+	pub n: i128,
+	pub d: i128,
+}
+
+pub fn make_rational (n: i128, d: i128) -> Option<Rational> {
+	if d == 0 { return None }
+	let g = gcd(n, d);
+	let sign = if d < 0 { -1 } else { 1 };
+	return Some(Rational { n: sign * n / g, d: sign * d / g });
+}
+
+pub fn integer (n: i128) -> Rational { Rational { n, d: 1 } }
+
+// the fraction a float was rounded from: the first convergent of its continued
+// fraction which is within 1e-14, if its denominator is at most "largest"
+fn closest_fraction (value: f64, largest: i128) -> Option<Rational> {
+	if !value.is_finite() || value.abs() > 1.0e12 { return None }
+	// the last two convergents, h / k
+	let (mut h0, mut h1, mut k0, mut k1): (i128, i128, i128, i128) = (0, 1, 1, 0);
+	let mut rest = value;
+	for _i in 0..64 {
+		let term = rest.floor();
+		let h = (term as i128).checked_mul(h1)?.checked_add(h0)?;
+		let k = (term as i128).checked_mul(k1)?.checked_add(k0)?;
+		if k > largest { return None }
+		if (h as f64 / k as f64 - value).abs() <= 1.0e-14 { return make_rational(h, k) }
+		h0 = h1; h1 = h; k0 = k1; k1 = k;
+		if rest == term { return None }
+		rest = 1.0 / (rest - term);
+	}
+	return None;
+}
+
+// a float as a fraction with a denominator of at most 2^16, like 3/8 or the
+// 2/3 of rectangle:1.5. an irrational float like pi is None
+pub fn from_f64 (value: f64) -> Option<Rational> {
+	return closest_fraction(value, 1 << 16);
+}
+
+// a float as a + b sqrt(r), with b at most 2 and both small fractions, like
+// the triangle's sqrt(3) / 2
+pub fn surd_from_f64 (value: f64, r: i128) -> Option<Surd> {
+	if let Some(a) = from_f64(value) { return Some(fraction(a)) }
+	let root = (r as f64).sqrt();
+	for d in 1..=8 {
+		for n in (-2 * d)..=(2 * d) {
+			if n == 0 || gcd(n, d) != 1 { continue }
+			let b = make_rational(n, d)?;
+			if let Some(a) = closest_fraction(value - b.to_f64() * root, 1 << 10) {
+				return Some(make_surd(a, b, r));
+			}
+		}
+	}
+	return None;
+}
+
+// the square root of a fraction, if it's a fraction
+fn rational_sqrt (q: &Rational) -> Option<Rational> {
+	return make_rational(integer_sqrt(q.n)?, integer_sqrt(q.d)?);
+}
+
+impl Rational {
+	pub fn is_zero (&self) -> bool { self.n == 0 }
+	pub fn signum (&self) -> i128 { self.n.signum() }
+	pub fn to_f64 (&self) -> f64 { self.n as f64 / self.d as f64 }
+	pub fn neg (&self) -> Rational { Rational { n: -self.n, d: self.d } }
+	pub fn add (&self, other: &Rational) -> Option<Rational> {
+		let g = gcd(self.d, other.d);
+		let a = self.n.checked_mul(other.d / g)?;
+		let b = other.n.checked_mul(self.d / g)?;
+		return make_rational(a.checked_add(b)?, (self.d / g).checked_mul(other.d)?);
+	}
+	pub fn sub (&self, other: &Rational) -> Option<Rational> {
+		return self.add(&other.neg());
+	}
+	pub fn mul (&self, other: &Rational) -> Option<Rational> {
+		// the denominators are positive, neither is ever 0
+		let g1 = gcd(self.n, other.d);
+		let g2 = gcd(other.n, self.d);
+		let n = (self.n / g1).checked_mul(other.n / g2)?;
+		let d = (self.d / g2).checked_mul(other.d / g1)?;
+		return make_rational(n, d);
+	}
+	pub fn div (&self, other: &Rational) -> Option<Rational> {
+		if other.is_zero() { return None }
+		return self.mul(&Rational { n: other.d, d: other.n }.normal());
+	}
+	fn normal (&self) -> Rational {
+		return if self.d < 0 { Rational { n: -self.n, d: -self.d } } else { *self };
+	}
+	// by value. the whole parts decide, or else the fractional parts' inverses
+	// do, the other way around. that's the continued fractions of both, the
+	// numbers only get smaller so nothing overflows
+	pub fn compare (&self, other: &Rational) -> Ordering {
+		let (mut a, mut b) = ((self.n, self.d), (other.n, other.d));
+		let mut flipped = false;
+		loop {
+			let (whole_a, whole_b) = (a.0.div_euclid(a.1), b.0.div_euclid(b.1));
+			let (rest_a, rest_b) = (a.0.rem_euclid(a.1), b.0.rem_euclid(b.1));
+			let order = if whole_a != whole_b { whole_a.cmp(&whole_b) }
+				else if rest_a == 0 || rest_b == 0 { rest_a.cmp(&rest_b) }
+				else {
+					// rest_a / a.1 against rest_b / b.1 is b.1 / rest_b against a.1 / rest_a
+					a = (a.1, rest_a);
+					b = (b.1, rest_b);
+					flipped = !flipped;
+					continue;
+				};
+			return if flipped { order.reverse() } else { order };
+		}
+	}
+}
+
+impl fmt::Display for Rational {
+	fn fmt (&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.d == 1 { write!(f, "{}", self.n) } else { write!(f, "{}/{}", self.n, self.d) }
+	}
+}
+
+// a + b sqrt(r). a fraction has b = 0, and then r = 0 too, so every number
+// has one form and equal numbers are equal structs
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Surd {
+	pub a: Rational,
+	pub b: Rational,
+	pub r: i128,
+}
+
+pub fn make_surd (a: Rational, b: Rational, r: i128) -> Surd {
+	return Surd { a, b, r: if b.is_zero() { 0 } else { r } };
+}
+
+pub fn fraction (a: Rational) -> Surd { Surd { a, b: integer(0), r: 0 } }
+
+impl Surd {
+	pub fn is_zero (&self) -> bool { self.a.is_zero() && self.b.is_zero() }
+	pub fn to_f64 (&self) -> f64 { self.a.to_f64() + self.b.to_f64() * (self.r as f64).sqrt() }
+	pub fn neg (&self) -> Surd { Surd { a: self.a.neg(), b: self.b.neg(), r: self.r } }
+	// the r of both, None if they have different ones
+	fn field (&self, other: &Surd) -> Option<i128> {
+		if self.r == 0 { return Some(other.r) }
+		if other.r == 0 || other.r == self.r { return Some(self.r) }
+		return None;
+	}
+	pub fn add (&self, other: &Surd) -> Option<Surd> {
+		let r = self.field(other)?;
+		return Some(make_surd(self.a.add(&other.a)?, self.b.add(&other.b)?, r));
+	}
+	pub fn sub (&self, other: &Surd) -> Option<Surd> {
+		return self.add(&other.neg());
+	}
+	pub fn mul (&self, other: &Surd) -> Option<Surd> {
+		let r = self.field(other)?;
+		let a = self.a.mul(&other.a)?.add(&self.b.mul(&other.b)?.mul(&integer(r))?)?;
+		let b = self.a.mul(&other.b)?.add(&self.b.mul(&other.a)?)?;
+		return Some(make_surd(a, b, r));
+	}
+	// a^2 - r b^2, the product with a - b sqrt(r). it's only 0 for 0, r
+	// isn't a square
+	fn norm (&self) -> Option<Rational> {
+		return self.a.mul(&self.a)?.sub(&self.b.mul(&self.b)?.mul(&integer(self.r))?);
+	}
+	pub fn div (&self, other: &Surd) -> Option<Surd> {
+		if other.is_zero() { return None }
+		let norm = other.norm()?;
+		let conjugate = Surd { a: other.a, b: other.b.neg(), r: other.r };
+		let product = self.mul(&conjugate)?;
+		return Some(make_surd(product.a.div(&norm)?, product.b.div(&norm)?, product.r));
+	}
+	// -1, 0 or 1. when a and b have different signs, the larger of a^2 and
+	// r b^2 wins
+	pub fn signum (&self) -> Option<i128> {
+		let (a, b) = (self.a.signum(), self.b.signum());
+		if b == 0 || a == b { return Some(a) }
+		if a == 0 { return Some(b) }
+		return Some(if self.norm()?.signum() > 0 { a } else { b });
+	}
+	// by value. None if the difference doesn't fit
+	pub fn compare (&self, other: &Surd) -> Option<Ordering> {
+		if self.b == other.b && self.r == other.r { return Some(self.a.compare(&other.a)) }
+		return Some(self.sub(other)?.signum()?.cmp(&0));
+	}
+	// the positive square root, if it's in the field of this r. None if the
+	// number is negative
+	pub fn sqrt (&self, r: i128) -> Made<Surd> {
+		return made(self.try_sqrt(r));
+	}
+	fn try_sqrt (&self, r: i128) -> Option<Made<Surd>> {
+		let sign = self.signum()?;
+		if sign < 0 { return Some(Made::None) }
+		if sign == 0 { return Some(Made::Some(*self)) }
+		if self.b.is_zero() {
+			if let Some(x) = rational_sqrt(&self.a) { return Some(Made::Some(fraction(x))) }
+			// a = r y^2, the root is y sqrt(r)
+			if let Some(y) = rational_sqrt(&self.a.div(&integer(r))?) {
+				return Some(Made::Some(make_surd(integer(0), y, r)));
+			}
+			return Some(Made::Outside);
+		}
+		// (x + y sqrt(r))^2 = x^2 + r y^2 + 2 x y sqrt(r), so x^2 + r y^2 = a
+		// and 2 x y = b. then x^2 is (a + m) / 2 or (a - m) / 2, m^2 = a^2 - r b^2
+		let m = match rational_sqrt(&self.norm()?) {
+			Some(m) => m,
+			None => return Some(Made::Outside),
+		};
+		for m in [m, m.neg()].iter() {
+			let x = match rational_sqrt(&self.a.add(m)?.div(&integer(2))?) {
+				Some(x) => x,
+				None => continue,
+			};
+			if x.is_zero() { continue }
+			let root = make_surd(x, self.b.div(&x.mul(&integer(2))?)?, self.r);
+			return Some(Made::Some(if root.signum()? < 0 { root.neg() } else { root }));
+		}
+		return Some(Made::Outside);
+	}
+}
+
+// like 1/2+1/2*sqrt(3), 1-sqrt(2) or 3
+impl fmt::Display for Surd {
+	fn fmt (&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.b.is_zero() { return write!(f, "{}", self.a) }
+		let root = match (self.b.n, self.b.d) {
+			(1, 1) => format!("sqrt({})", self.r),
+			(-1, 1) => format!("-sqrt({})", self.r),
+			_ => format!("{}*sqrt({})", self.b, self.r),
+		};
+		if self.a.is_zero() { return write!(f, "{}", root) }
+		if self.b.signum() < 0 { write!(f, "{}{}", self.a, root) } else { write!(f, "{}+{}", self.a, root) }
+	}
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ExactPoint {
+	pub x: Surd,
+	pub y: Surd,
+}
+
+// a x + b y = c, scaled so the first of a and b which isn't zero is 1.
+// every line has one form, equal lines are equal structs
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ExactLine {
+	pub a: Surd,
+	pub b: Surd,
+	pub c: Surd,
+}
+
+fn make_line (a: Surd, b: Surd, c: Surd) -> Option<ExactLine> {
+	let scale = if !a.is_zero() { a } else if !b.is_zero() { b } else { return None };
+	return Some(ExactLine { a: a.div(&scale)?, b: b.div(&scale)?, c: c.div(&scale)? });
+}
+
+fn constant (n: i128) -> Surd { fraction(integer(n)) }
+
+impl ExactPoint {
+	pub fn to_vector (&self) -> Vector { Vector { x: self.x.to_f64(), y: self.y.to_f64() } }
+}
+
+impl ExactLine {
+	// a x + b y - c, which side of the line a point is on
+	fn side (&self, p: &ExactPoint) -> Option<Surd> {
+		return self.a.mul(&p.x)?.add(&self.b.mul(&p.y)?)?.sub(&self.c);
+	}
+	// a^2 + b^2
+	fn normal_squared (&self) -> Option<Surd> {
+		return self.a.mul(&self.a)?.add(&self.b.mul(&self.b)?);
+	}
+	pub fn to_line (&self) -> Line {
+		let (a, b, c) = (self.a.to_f64(), self.b.to_f64(), self.c.to_f64());
+		let length = (a * a + b * b).sqrt();
+		return Line { u: Vector { x: a / length, y: b / length }, d: c / length };
+	}
+}
+
+// the point mirrored across the line
+pub fn reflect (l: &ExactLine, p: &ExactPoint) -> Option<ExactPoint> {
+	let t = l.side(p)?.mul(&constant(2))?.div(&l.normal_squared()?)?;
+	return Some(ExactPoint { x: p.x.sub(&t.mul(&l.a)?)?, y: p.y.sub(&t.mul(&l.b)?)? });
+}
+
+// the line through two points
+pub fn axiom1 (p: &ExactPoint, q: &ExactPoint) -> Option<ExactLine> {
+	let a = q.y.sub(&p.y)?.neg();
+	let b = q.x.sub(&p.x)?;
+	let c = a.mul(&p.x)?.add(&b.mul(&p.y)?)?;
+	return make_line(a, b, c);
+}
+
+// the line which folds one point onto the other
+pub fn axiom2 (p: &ExactPoint, q: &ExactPoint) -> Option<ExactLine> {
+	let a = q.x.sub(&p.x)?;
+	let b = q.y.sub(&p.y)?;
+	let two = constant(2);
+	let mx = p.x.add(&q.x)?.div(&two)?;
+	let my = p.y.add(&q.y)?.div(&two)?;
+	let c = a.mul(&mx)?.add(&b.mul(&my)?)?;
+	return make_line(a, b, c);
+}
+
+// the lines which fold one line onto the other: the bisectors of their
+// angle, or the line halfway between them if they are parallel. scaling the
+// lines to unit normals takes a square root of each, in the field of r
+pub fn axiom3 (l1: &ExactLine, l2: &ExactLine, r: i128) -> Made<Vec<ExactLine>> {
+	return made(try_axiom3(l1, l2, r));
+}
+
+fn try_axiom3 (l1: &ExactLine, l2: &ExactLine, r: i128) -> Option<Made<Vec<ExactLine>>> {
+	if l1 == l2 { return Some(Made::None) }
+	let n1 = match l1.normal_squared()?.sqrt(r) { Made::Some(n) => n, other => return Some(other.failed()) };
+	let n2 = match l2.normal_squared()?.sqrt(r) { Made::Some(n) => n, other => return Some(other.failed()) };
+	let mut lines = Vec::new();
+	for s in [1, -1].iter() {
+		let s = constant(*s);
+		let a = l1.a.div(&n1)?.sub(&l2.a.div(&n2)?.mul(&s)?)?;
+		let b = l1.b.div(&n1)?.sub(&l2.b.div(&n2)?.mul(&s)?)?;
+		// parallel lines cancel in one of the two
+		if a.is_zero() && b.is_zero() { continue }
+		let c = l1.c.div(&n1)?.sub(&l2.c.div(&n2)?.mul(&s)?)?;
+		lines.push(make_line(a, b, c)?);
+	}
+	return Some(Made::Some(lines));
+}
+
+// the line through a point, perpendicular to a line
+pub fn axiom4 (p: &ExactPoint, l: &ExactLine) -> Option<ExactLine> {
+	let (a, b) = (l.b.neg(), l.a);
+	let c = a.mul(&p.x)?.add(&b.mul(&p.y)?)?;
+	return make_line(a, b, c);
+}
+
+// the lines through p2 which fold p1 onto l. p1 lands where the circle
+// around p2 through p1 crosses l, which takes a square root, in the field of r
+pub fn axiom5 (p1: &ExactPoint, p2: &ExactPoint, l: &ExactLine, r: i128) -> Made<Vec<ExactLine>> {
+	return made(try_axiom5(p1, p2, l, r));
+}
+
+fn try_axiom5 (p1: &ExactPoint, p2: &ExactPoint, l: &ExactLine, r: i128) -> Option<Made<Vec<ExactLine>>> {
+	let normal = l.normal_squared()?;
+	// the foot of p2 on l, and the squared distance to it
+	let t = l.side(p2)?.div(&normal)?;
+	let foot = ExactPoint { x: p2.x.sub(&t.mul(&l.a)?)?, y: p2.y.sub(&t.mul(&l.b)?)? };
+	let height = t.mul(&l.side(p2)?)?;
+	let (dx, dy) = (p1.x.sub(&p2.x)?, p1.y.sub(&p2.y)?);
+	let radius = dx.mul(&dx)?.add(&dy.mul(&dy)?)?;
+	// the crossings are s steps of (-b, a) along l from the foot
+	let s = match radius.sub(&height)?.div(&normal)?.sqrt(r) { Made::Some(s) => s, other => return Some(other.failed()) };
+	let steps = if s.is_zero() { vec![s] } else { vec![s, s.neg()] };
+	let mut lines = Vec::new();
+	for step in steps.iter() {
+		let q = ExactPoint { x: foot.x.sub(&step.mul(&l.b)?)?, y: foot.y.add(&step.mul(&l.a)?)? };
+		if q == *p1 { continue }
+		lines.push(axiom2(p1, &q)?);
+	}
+	return Some(Made::Some(lines));
+}
+
+// the line perpendicular to l2 which folds a point onto l1. None if l1 and
+// l2 are perpendicular, the point never meets l1
+pub fn axiom7 (p: &ExactPoint, l1: &ExactLine, l2: &ExactLine) -> Option<ExactLine> {
+	// the crease's normal is along l2
+	let (ux, uy) = (l2.b.neg(), l2.a);
+	let denominator = l1.a.mul(&ux)?.add(&l1.b.mul(&uy)?)?;
+	if denominator.is_zero() { return None }
+	// p moves along u by 2t to reach l1, the crease crosses halfway
+	let t = l1.side(p)?.neg().div(&denominator.mul(&constant(2))?)?;
+	let mx = p.x.add(&t.mul(&ux)?)?;
+	let my = p.y.add(&t.mul(&uy)?)?;
+	let c = ux.mul(&mx)?.add(&uy.mul(&my)?)?;
+	return make_line(ux, uy, c);
+}
+
+pub fn intersect (l1: &ExactLine, l2: &ExactLine) -> Option<ExactPoint> {
+	let det = l1.a.mul(&l2.b)?.sub(&l1.b.mul(&l2.a)?)?;
+	if det.is_zero() { return None }
+	let x = l1.c.mul(&l2.b)?.sub(&l1.b.mul(&l2.c)?)?.div(&det)?;
+	let y = l1.a.mul(&l2.c)?.sub(&l1.c.mul(&l2.a)?)?.div(&det)?;
+	return Some(ExactPoint { x, y });
+}
+
+// the outcome of one construction
+#[derive(PartialEq, Debug)]
+pub enum Made<T> {
+	Some(T),
+	None, // there is no solution, or it misses the paper
+	Overflow, // the numbers don't fit in i128 fractions
+	Outside, // it needs a square root which isn't in the field
+}
+
+// the arithmetic's None is an overflow
+fn made<T> (outcome: Option<Made<T>>) -> Made<T> {
+	return outcome.unwrap_or(Made::Overflow);
+}
+
+impl<T> Made<T> {
+	// a step without a result, as the outcome of the construction it was for
+	fn failed<U> (self) -> Made<U> {
+		match self {
+			Made::Overflow => Made::Overflow,
+			Made::Outside => Made::Outside,
+			_ => Made::None,
+		}
+	}
+}
+
+// entries in the order they were first made, with their count, axiom
+// (0 for points) and round
+struct Counter<T> {
+	list: Vec<(T, u64, u8, u8)>,
+	index: HashMap<T, usize>,
+}
+
+fn make_counter<T: Copy + Eq + std::hash::Hash> () -> Counter<T> {
+	return Counter { list: Vec::new(), index: HashMap::new() };
+}
+
+impl<T: Copy + Eq + std::hash::Hash> Counter<T> {
+	fn increment_match (&mut self, key: &T) -> bool {
+		match self.index.get(key) {
+			Some(i) => { self.list[*i].1 += 1; true }
+			None => false,
+		}
+	}
+	fn push (&mut self, key: T, axiom: u8, round: u8) {
+		self.index.insert(key, self.list.len());
+		self.list.push((key, 1, axiom, round));
+	}
+	fn filter (&self, count: u64) -> Vec<T> {
+		return self.list.iter().filter(|el| el.1 >= count).map(|el| el.0).collect();
+	}
+	fn merge (&mut self, other: Counter<T>) {
+		for entry in other.list {
+			self.index.insert(entry.0, self.list.len());
+			self.list.push(entry);
+		}
+	}
+}
+
+// the paper, and every point and line made so far
+pub struct ExactState {
+	pub vertices: Vec<ExactPoint>,
+	// every number is a + b sqrt(r)
+	pub r: i128,
+	points: Counter<ExactPoint>,
+	lines: Counter<ExactLine>,
+}
+
+// the paper's corners and sides, like make_state. the field is the first of
+// RADICANDS which has every corner. None if none has, like for a pentagon
+pub fn make_exact_state (paper: &Polygon) -> Option<ExactState> {
+	let r = *RADICANDS.iter().find(|r| paper.vertices.iter()
+		.all(|v| surd_from_f64(v.x, **r).is_some() && surd_from_f64(v.y, **r).is_some()))?;
+	let mut vertices: Vec<ExactPoint> = Vec::new();
+	for v in paper.vertices.iter() {
+		vertices.push(ExactPoint { x: surd_from_f64(v.x, r)?, y: surd_from_f64(v.y, r)? });
+	}
+	let mut state = ExactState { vertices, r, points: make_counter(), lines: make_counter() };
+	let n = state.vertices.len();
+	for i in 0..n {
+		let vertex = state.vertices[i];
+		state.points.push(vertex, 0, 0);
+	}
+	for i in 0..n {
+		let side = axiom1(&state.vertices[i], &state.vertices[(i + 1) % n])?;
+		state.lines.push(side, 0, 0);
+	}
+	return Some(state);
+}
+
+impl ExactState {
+	// inside the paper or on its edge. the corners are counterclockwise
+	pub fn contains (&self, p: &ExactPoint) -> Option<bool> {
+		let n = self.vertices.len();
+		for i in 0..n {
+			let (a, b) = (self.vertices[i], self.vertices[(i + 1) % n]);
+			let cross = b.x.sub(&a.x)?.mul(&p.y.sub(&a.y)?)?
+				.sub(&b.y.sub(&a.y)?.mul(&p.x.sub(&a.x)?)?)?;
+			if cross.signum()? < 0 { return Some(false) }
+		}
+		return Some(true);
+	}
+	// the line crosses the paper, or lies along a side. like Polygon::clip,
+	// a line which only touches a corner misses
+	pub fn crosses (&self, line: &ExactLine) -> Option<bool> {
+		let (mut below, mut above, mut on) = (false, false, 0);
+		for v in self.vertices.iter() {
+			match line.side(v)?.signum()? {
+				-1 => below = true,
+				1 => above = true,
+				_ => on += 1,
+			}
+		}
+		return Some((below && above) || on >= 2);
+	}
+	// the fold crosses the paper, and every moving point lands on it, like
+	// Polygon::keep_folds. a point the fold leaves where it is isn't folded
+	fn keep (&self, line: ExactLine, moving: &[ExactPoint]) -> Made<ExactLine> {
+		for p in moving.iter() {
+			let landed = match reflect(&line, p) {
+				Some(landed) => landed,
+				None => return Made::Overflow,
+			};
+			if landed == *p { return Made::None }
+			match self.contains(&landed) {
+				Some(true) => {},
+				Some(false) => return Made::None,
+				None => return Made::Overflow,
+			}
+		}
+		match self.crosses(&line) {
+			Some(true) => Made::Some(line),
+			Some(false) => Made::None,
+			None => Made::Overflow,
+		}
+	}
+	pub fn points_len (&self) -> usize { self.points.list.len() }
+	pub fn lines_len (&self) -> usize { self.lines.list.len() }
+	// the largest denominator in any point's coordinates
+	pub fn largest_denominator (&self) -> i128 {
+		return self.points.list.iter()
+			.map(|el| [el.0.x.a.d, el.0.x.b.d, el.0.y.a.d, el.0.y.b.d].iter().cloned().max().unwrap())
+			.max().unwrap_or(1);
+	}
+	// the points and lines as floats, in the containers the drawing uses.
+	// points which are different but closer than the tolerance stay apart
+	pub fn to_containers (&self) -> (GridVec, LineContainer) {
+		let mut points: GridVec = make_grid();
+		let mut lines: LineContainer = make_line_container();
+		for el in self.points.list.iter() {
+			let point: CountPoint = (el.0.to_vector(), el.1, el.3);
+			points.push_counted(point, &vec![]);
+		}
+		for el in self.lines.list.iter() {
+			let line: CountLine = (el.0.to_line(), el.1, el.2, el.3);
+			lines.push_counted(line, &vec![]);
+		}
+		return (points, lines);
+	}
+	// x, y, count, round, with the coordinates like 1/2+1/2*sqrt(3). sorted
+	// by x, then y. None if two coordinates are too large to compare
+	pub fn points_csv (&self) -> Option<String> {
+		let mut list = self.points.list.clone();
+		let mut overflowed = false;
+		list.sort_by(|a, b| match (a.0.x.compare(&b.0.x), a.0.y.compare(&b.0.y)) {
+			(Some(x), Some(y)) => x.then(y),
+			_ => { overflowed = true; Ordering::Equal }
+		});
+		if overflowed { return None }
+		let mut table: String = String::new();
+		table.push_str("x,y,count,round\n");
+		for el in list.iter() {
+			table.push_str(&format!("{},{},{},{}\n", el.0.x, el.0.y, el.1, el.3));
+		}
+		return Some(table);
+	}
+}
+
+// why a construction was lost, which stops the round
+fn lost<T> (made: &Made<T>, axiom: u8, r: i128) -> String {
+	match *made {
+		Made::Outside => format!("axiom {} needs a square root which isn't a + b sqrt({})", axiom, r),
+		_ => format!("axiom {} made numbers which don't fit in 128 bit fractions", axiom),
+	}
+}
+
+// count the solutions of one construction, like add_solutions in make.rs.
+// the moving points are the ones the fold carries, which have to stay on the paper
+fn add_solutions (
+	state: &mut ExactState,
+	new_lines: &mut Counter<ExactLine>,
+	made: Made<Vec<ExactLine>>,
+	moving: &[ExactPoint],
+	axiom: u8,
+	round: u8
+) -> Result<(), String> {
+	let solutions = match made {
+		Made::Some(solutions) => solutions,
+		Made::None => return Ok(()),
+		failed => return Err(lost(&failed, axiom, state.r)),
+	};
+	for solution in solutions {
+		let line = match state.keep(solution, moving) {
+			Made::Some(line) => line,
+			Made::None => continue,
+			failed => return Err(lost(&failed, axiom, state.r)),
+		};
+		if state.lines.increment_match(&line) { continue }
+		if new_lines.increment_match(&line) { continue }
+		new_lines.push(line, axiom, round);
+	}
+	return Ok(());
+}
+
+// the same round as make_round, with the axioms exact mode can make.
+// the axioms are built in the same order, so the counts are what make_round
+// would count if every number were exact. a construction which can't be made
+// exactly is an error, the counts would be short without it
+pub fn make_round (round: usize, plan: &RoundPlan, state: &mut ExactState) -> Result<(), String> {
+	assert!(round < MAX_ROUNDS, "round {} can't be marked, there are at most {} rounds", round, MAX_ROUNDS);
+	let mark = (round + 1) as u8;
+	let r = state.r;
+	let mut new_lines: Counter<ExactLine> = make_counter();
+	// axioms which share a filter share the list, filtered the first time
+	// it's needed, as make_round does. later axioms raise the old counts
+	let mut filtered_points: Vec<(u64, Vec<ExactPoint>)> = Vec::new();
+	let mut filtered_lines: Vec<(u64, Vec<ExactLine>)> = Vec::new();
+	for axiom in plan.axioms.iter() {
+		if !EXACT_AXIOMS.contains(axiom) {
+			return Err(format!("exact arithmetic can't make axiom {}", axiom));
+		}
+		let (min_points, min_lines) = (plan.min_points(*axiom), plan.min_lines(*axiom));
+		if !filtered_points.iter().any(|el| el.0 == min_points) {
+			filtered_points.push((min_points, state.points.filter(min_points)));
+		}
+		if !filtered_lines.iter().any(|el| el.0 == min_lines) {
+			filtered_lines.push((min_lines, state.lines.filter(min_lines)));
+		}
+		let points = filtered_points.iter().find(|el| el.0 == min_points).unwrap().1.clone();
+		let lines = filtered_lines.iter().find(|el| el.0 == min_lines).unwrap().1.clone();
+		match *axiom {
+			1 | 2 => for i in 0..points.len() {
+				for j in (i + 1)..points.len() {
+					let line = if *axiom == 1 { axiom1(&points[i], &points[j]) }
+						else { axiom2(&points[i], &points[j]) };
+					add_solutions(state, &mut new_lines, solved(line), &[], *axiom, mark)?;
+				}
+			},
+			3 => for i in 0..lines.len() {
+				for j in (i + 1)..lines.len() {
+					let made = axiom3(&lines[i], &lines[j], r);
+					add_solutions(state, &mut new_lines, made, &[], 3, mark)?;
+				}
+			},
+			4 => for i in 0..points.len() {
+				for j in 0..lines.len() {
+					let made = solved(axiom4(&points[i], &lines[j]));
+					add_solutions(state, &mut new_lines, made, &[], 4, mark)?;
+				}
+			},
+			5 => for i in 0..points.len() {
+				for j in 0..points.len() {
+					if i == j { continue }
+					for k in 0..lines.len() {
+						// the fold passes through points[j], only points[i] moves
+						let made = axiom5(&points[i], &points[j], &lines[k], r);
+						add_solutions(state, &mut new_lines, made, &[points[i]], 5, mark)?;
+					}
+				}
+			},
+			7 => for i in 0..points.len() {
+				for j in 0..lines.len() {
+					for k in 0..lines.len() {
+						if j == k { continue }
+						let made = axiom7_made(&points[i], &lines[j], &lines[k]);
+						add_solutions(state, &mut new_lines, made, &[points[i]], 7, mark)?;
+					}
+				}
+			},
+			_ => {}
+		}
+	}
+	let mut new_points: Counter<ExactPoint> = make_counter();
+	if plan.intersections {
+		let old_lines: Vec<(ExactLine, u8)> = state.lines.list.iter().map(|el| (el.0, el.3)).collect();
+		for i in 0..new_lines.list.len() {
+			let line = new_lines.list[i].0;
+			for j in (i + 1)..new_lines.list.len() {
+				let other = new_lines.list[j].0;
+				add_intersection(state, &mut new_points, &line, &other, mark)?;
+			}
+			for j in 0..old_lines.len() {
+				add_intersection(state, &mut new_points, &line, &old_lines[j].0, mark)?;
+			}
+		}
+	}
+	state.points.merge(new_points);
+	state.lines.merge(new_lines);
+	return Ok(());
+}
+
+// axioms 1, 2 and 4 always have a solution for two different points, or a
+// point and a line, so when they return None their numbers overflowed
+fn solved (line: Option<ExactLine>) -> Made<Vec<ExactLine>> {
+	match line {
+		Some(line) => Made::Some(vec![line]),
+		None => Made::Overflow,
+	}
+}
+
+// axiom 7 has no solution when the lines are perpendicular, tell that apart
+// from an overflow
+fn axiom7_made (p: &ExactPoint, l1: &ExactLine, l2: &ExactLine) -> Made<Vec<ExactLine>> {
+	let (ux, uy) = (l2.b.neg(), l2.a);
+	let denominator = match l1.a.mul(&ux).and_then(|a| l1.b.mul(&uy).and_then(|b| a.add(&b))) {
+		Some(denominator) => denominator,
+		None => return Made::Overflow,
+	};
+	if denominator.is_zero() { return Made::None }
+	return solved(axiom7(p, l1, l2));
+}
+
+fn add_intersection (
+	state: &mut ExactState,
+	new_points: &mut Counter<ExactPoint>,
+	line: &ExactLine,
+	other: &ExactLine,
+	round: u8
+) -> Result<(), String> {
+	let overflow = "an intersection made numbers which don't fit in 128 bit fractions".to_string();
+	// parallel lines have a zero determinant, anything else which fails overflowed
+	let parallel = match line.a.mul(&other.b).and_then(|a| other.a.mul(&line.b).and_then(|b| a.sub(&b))) {
+		Some(det) => det.is_zero(),
+		None => return Err(overflow),
+	};
+	if parallel { return Ok(()) }
+	let point = match intersect(line, other) {
+		Some(point) => point,
+		None => return Err(overflow),
+	};
+	match state.contains(&point) {
+		Some(true) => {},
+		Some(false) => return Ok(()),
+		None => return Err(overflow),
+	}
+	if state.points.increment_match(&point) { return Ok(()) }
+	if new_points.increment_match(&point) { return Ok(()) }
+	new_points.push(point, 0, round);
+	return Ok(());
+}
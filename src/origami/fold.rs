@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use rabbit_ear as ear;
+use self::ear::Vector;
+use self::ear::Segment;
+use origami::CountPoint;
+use origami::CountSegment;
+use origami::Polygon;
+use origami::Tolerance;
+
+// a FOLD crease pattern (https://github.com/edemaine/fold). every segment is
+// split where it crosses another, so the edges form a planar graph.
+// the paper's sides are boundary edges "B", every other crease is
+// unassigned "U". the faces are the pieces the creases cut the paper into,
+// counter-clockwise. the coordinates are in the paper's units, the ones
+// --find-point takes and prints, so "frame_unit" is "unit". the custom fields in the "axioms" namespace keep the counts:
+//   vertices_axioms:count  times the point was made, 0 if it wasn't drawn
+//   edges_axioms:count     times the edge's line was made
+//   edges_axioms:axiom     the axiom which first made the line, 0 for the paper
+//   edges_axioms:round     the round the line first appears in
+
+// how far past a segment's ends a crossing still counts
+const EPSILON: f64 = 1.0e-10;
+
+fn cross (a: Vector, b: Vector) -> f64 { a.x * b.y - a.y * b.x }
+
+fn minus (a: Vector, b: Vector) -> Vector { Vector { x: a.x - b.x, y: a.y - b.y } }
+
+// vertices without repeats, the index of a vertex is its place in the list.
+// vertices closer than the tolerance are one, as points are in the indices
+struct Vertices {
+	list: Vec<Vector>,
+	cells: BTreeMap<(i64, i64), Vec<usize>>,
+	tolerance: Tolerance,
+}
+
+impl Vertices {
+	fn cell (&self, point: Vector) -> (i64, i64) {
+		let cells = self.tolerance.point_cells();
+		return ((point.x * cells).floor() as i64, (point.y * cells).floor() as i64);
+	}
+	fn find (&self, point: Vector) -> Option<usize> {
+		let (i, j) = self.cell(point);
+		for di in -1..2 {
+			for dj in -1..2 {
+				let cell = match self.cells.get(&(i + di, j + dj)) { Some(cell) => cell, None => continue };
+				for k in cell.iter() {
+					if self.tolerance.within(self.tolerance.point_distance(&point, &self.list[*k])) { return Some(*k) }
+				}
+			}
+		}
+		return None;
+	}
+	fn index (&mut self, point: Vector) -> usize {
+		if let Some(k) = self.find(point) { return k }
+		let cell = self.cell(point);
+		self.cells.entry(cell).or_insert_with(Vec::new).push(self.list.len());
+		self.list.push(point);
+		return self.list.len() - 1;
+	}
+}
+
+// where two segments cross, as the distance along each (0 to 1)
+fn crossing (a: &Segment, b: &Segment) -> Option<(f64, f64)> {
+	let (da, db) = (minus(a.b, a.a), minus(b.b, b.a));
+	let denominator = cross(da, db);
+	if denominator.abs() < EPSILON { return None }
+	let between = minus(b.a, a.a);
+	let t = cross(between, db) / denominator;
+	let s = cross(between, da) / denominator;
+	if t < -EPSILON || t > 1.0 + EPSILON || s < -EPSILON || s > 1.0 + EPSILON { return None }
+	return Some((t, s));
+}
+
+fn finite (segment: &Segment) -> bool {
+	segment.a.x.is_finite() && segment.a.y.is_finite() && segment.b.x.is_finite() && segment.b.y.is_finite()
+}
+
+// twice the signed area, positive if counter-clockwise
+fn area (vertices: &Vec<Vector>, face: &Vec<usize>) -> f64 {
+	let mut area = 0.0;
+	for i in 0..face.len() {
+		area += cross(vertices[face[i]], vertices[face[(i + 1) % face.len()]]);
+	}
+	return area;
+}
+
+// walking along an edge and turning onto the next edge clockwise around the
+// vertex it arrives at goes around the face on the edge's left. every face
+// inside goes counter-clockwise, the outside of the paper is the one which
+// goes clockwise
+fn faces (vertices: &Vec<Vector>, edges: &Vec<(usize, usize)>) -> Vec<Vec<usize>> {
+	let mut around: Vec<Vec<usize>> = vec![Vec::new(); vertices.len()];
+	for &(a, b) in edges.iter() {
+		around[a].push(b);
+		around[b].push(a);
+	}
+	for v in 0..vertices.len() {
+		let center = vertices[v];
+		let angle = |k: &usize| (vertices[*k].y - center.y).atan2(vertices[*k].x - center.x);
+		around[v].sort_by(|a, b| angle(a).total_cmp(&angle(b)));
+	}
+	let mut walked: HashSet<(usize, usize)> = HashSet::new();
+	let mut faces: Vec<Vec<usize>> = Vec::new();
+	for &(a, b) in edges.iter() {
+		for &(from, to) in [(a, b), (b, a)].iter() {
+			let mut face: Vec<usize> = Vec::new();
+			let (mut u, mut v) = (from, to);
+			while walked.insert((u, v)) {
+				face.push(u);
+				let i = around[v].iter().position(|k| *k == u).unwrap();
+				let next = around[v][(i + around[v].len() - 1) % around[v].len()];
+				u = v;
+				v = next;
+			}
+			if face.len() > 2 && area(vertices, &face) > EPSILON { faces.push(face); }
+		}
+	}
+	return faces;
+}
+
+fn numbers<T: ToString> (list: &Vec<T>) -> String {
+	let strings: Vec<String> = list.iter().map(|el| el.to_string()).collect();
+	return format!("[{}]", strings.join(","));
+}
+
+fn pairs<T: ToString> (list: &Vec<(T, T)>) -> String {
+	let strings: Vec<String> = list.iter()
+		.map(|el| format!("[{},{}]", el.0.to_string(), el.1.to_string()))
+		.collect();
+	return format!("[{}]", strings.join(","));
+}
+
+fn lists<T: ToString> (list: &Vec<Vec<T>>) -> String {
+	let strings: Vec<String> = list.iter().map(numbers).collect();
+	return format!("[{}]", strings.join(","));
+}
+
+// the segments and points are the ones drawn, the paper's sides are always
+// included. segments which aren't finite are left out. creases along a side
+// and vertices are the same within the tolerance
+pub fn fold_json (segments: &Vec<CountSegment>, points: &Vec<CountPoint>, paper: &Polygon, tolerance: &Tolerance) -> String {
+	// the boundary first, then every crease which isn't on the boundary.
+	// a crease along a side gives the side its count and round
+	let mut creases: Vec<(CountSegment, char)> = Vec::new();
+	for side in paper.sides.iter() {
+		let (success, segment) = paper.clip(*side);
+		if success { creases.push(((segment, 0, 0, 0), 'B')); }
+	}
+	let sides = creases.len();
+	for i in 0..segments.len() {
+		if !finite(&segments[i].0) { continue }
+		let side = (0..sides).find(|k| {
+			let line = &paper.sides[*k];
+			let a = &segments[i].0.a;
+			let b = &segments[i].0.b;
+			tolerance.within((a.x * line.u.x + a.y * line.u.y - line.d).abs())
+				&& tolerance.within((b.x * line.u.x + b.y * line.u.y - line.d).abs())
+		});
+		match side {
+			Some(k) => { (creases[k].0).1 = segments[i].1; (creases[k].0).3 = segments[i].3; }
+			None => creases.push((segments[i], 'U')),
+		}
+	}
+	// every place along each crease a vertex is: its ends and its crossings
+	let mut stops: Vec<Vec<(f64, Vector)>> = creases.iter()
+		.map(|el| vec![(0.0, (el.0).0.a), (1.0, (el.0).0.b)])
+		.collect();
+	for i in 0..creases.len() {
+		for j in (i + 1)..creases.len() {
+			let (a, b) = (&(creases[i].0).0, &(creases[j].0).0);
+			if let Some((t, s)) = crossing(a, b) {
+				let d = minus(a.b, a.a);
+				let point = Vector { x: a.a.x + d.x * t, y: a.a.y + d.y * t };
+				stops[i].push((t, point));
+				stops[j].push((s, point));
+			}
+		}
+	}
+	let mut vertices = Vertices { list: Vec::new(), cells: BTreeMap::new(), tolerance: *tolerance };
+	let mut edges: Vec<(usize, usize)> = Vec::new();
+	let mut assignments: Vec<String> = Vec::new();
+	let mut edge_counts: Vec<u64> = Vec::new();
+	let mut edge_axioms: Vec<u8> = Vec::new();
+	let mut edge_rounds: Vec<u8> = Vec::new();
+	for i in 0..creases.len() {
+		stops[i].sort_by(|a, b| a.0.total_cmp(&b.0));
+		let indices: Vec<usize> = stops[i].iter().map(|el| vertices.index(el.1)).collect();
+		for k in 1..indices.len() {
+			if indices[k - 1] == indices[k] { continue }
+			edges.push((indices[k - 1], indices[k]));
+			assignments.push(format!("\"{}\"", creases[i].1));
+			edge_counts.push((creases[i].0).1);
+			edge_axioms.push((creases[i].0).2);
+			edge_rounds.push((creases[i].0).3);
+		}
+	}
+	let mut vertex_counts: Vec<u64> = vec![0; vertices.list.len()];
+	for i in 0..points.len() {
+		if let Some(k) = vertices.find(points[i].0) { vertex_counts[k] = points[i].1; }
+	}
+	let coords: Vec<(f64, f64)> = vertices.list.iter()
+		.map(|v| paper.to_paper(*v))
+		.map(|v| (v.x, v.y))
+		.collect();
+	let faces = faces(&vertices.list, &edges);
+
+	let mut fold: String = String::new();
+	fold.push_str("{\n");
+	fold.push_str("\"file_spec\": 1.1,\n");
+	fold.push_str("\"file_creator\": \"axiom-drawings\",\n");
+	fold.push_str("\"file_classes\": [\"singleModel\"],\n");
+	fold.push_str("\"frame_classes\": [\"creasePattern\"],\n");
+	fold.push_str("\"frame_attributes\": [\"2D\"],\n");
+	fold.push_str("\"frame_unit\": \"unit\",\n");
+	fold.push_str(&format!("\"vertices_coords\": {},\n", pairs(&coords)));
+	fold.push_str(&format!("\"vertices_axioms:count\": {},\n", numbers(&vertex_counts)));
+	fold.push_str(&format!("\"edges_vertices\": {},\n", pairs(&edges)));
+	fold.push_str(&format!("\"edges_assignment\": [{}],\n", assignments.join(",")));
+	fold.push_str(&format!("\"edges_axioms:count\": {},\n", numbers(&edge_counts)));
+	fold.push_str(&format!("\"edges_axioms:axiom\": {},\n", numbers(&edge_axioms)));
+	fold.push_str(&format!("\"edges_axioms:round\": {},\n", numbers(&edge_rounds)));
+	fold.push_str(&format!("\"faces_vertices\": {}\n", lists(&faces)));
+	fold.push_str("}\n");
+	return fold;
+}
@@ -1,107 +1,256 @@
+use std::collections::BTreeMap;
 use rabbit_ear as ear;
 use self::ear::Vector;
 use origami::CountPoint;
+use origami::Origin;
+use origami::Provenance;
+use origami::PointIndex;
+use origami::Tolerance;
+use origami::EdgeCount;
+use origami::tolerance::default_tolerance;
+use origami::index::refile_points;
 
-const BUCKET: usize = 1000;
-const BUCKET_MAX_I: usize = 999;
-const BUCKET_F: f64 = 1000.0;
+type Cell = (i64, i64);
 
 // #[derive(Copy, Clone)]
+// a sparse grid of cells, keyed by (column, row). cells are kept in order so
+// flatten always lists the points in the same order.
+// when provenance is tracked, "origins" mirrors "buckets": origins[c][k] are
+// the constructions of the point buckets[c][k]. otherwise it's empty
+// "round" is the round given to points made with push.
+// the cells per unit length come from the tolerance, only cells containing
+// points are allocated
 pub struct GridVec {
-	pub buckets: Vec<Vec<Vec<CountPoint>>>
+	pub buckets: BTreeMap<Cell, Vec<CountPoint>>,
+	pub origins: BTreeMap<Cell, Vec<Vec<Origin>>>,
+	pub provenance: Provenance,
+	pub round: u8,
+	pub tolerance: Tolerance,
+	pub edges: EdgeCount
 }
 
-fn point_to_index (point: Vector) -> (usize, usize) {
-	let mut i: usize = (point.x * BUCKET_F).floor() as usize;
-	if i == BUCKET { i = BUCKET_MAX_I }
-	let mut j: usize = (point.y * BUCKET_F).floor() as usize;
-	if j == BUCKET { j = BUCKET_MAX_I }
+fn point_to_index (point: Vector, cells: f64) -> Cell {
+	let i = (point.x * cells).floor() as i64;
+	let j = (point.y * cells).floor() as i64;
 	return (i, j);
 }
 
+// the cell a point is in, and the three cells next to it on the sides the
+// point is closest to. any point less than half a cell away is in one of these
+fn nearby_cells (point: Vector, cells: f64) -> [Cell; 4] {
+	let (i, j) = point_to_index(point, cells);
+	let x = point.x * cells - i as f64;
+	let y = point.y * cells - j as f64;
+	let di = if x < 0.5 { -1 } else { 1 };
+	let dj = if y < 0.5 { -1 } else { 1 };
+	return [(i, j), (i + di, j), (i, j + dj), (i + di, j + dj)];
+}
+
 pub fn make_grid () -> GridVec {
-	let mut buckets: Vec<Vec<Vec<CountPoint>>> = Vec::new();
-	for i in 0..BUCKET {
-		let row: Vec<Vec<CountPoint>> = Vec::new();
-		buckets.push(row);
-		for _j in 0..BUCKET {
-			let col: Vec<CountPoint> = Vec::new();
-			buckets[i].push(col);
+	return make_tracked_grid(Provenance::None);
+}
+
+pub fn make_tracked_grid (provenance: Provenance) -> GridVec {
+	return GridVec {
+		buckets: BTreeMap::new(),
+		origins: BTreeMap::new(),
+		provenance,
+		round: 0,
+		tolerance: default_tolerance(),
+		edges: EdgeCount::default()
+	};
+}
+
+impl GridVec {
+	// the cell and index of the first point equivalent to this one, and how
+	// far it is. without a match, how far the nearest point searched is
+	fn find (&self, point: &Vector) -> (Option<(Cell, usize)>, f64) {
+		let mut nearest = std::f64::INFINITY;
+		for cell in nearby_cells(*point, self.tolerance.point_cells()).iter() {
+			let bucket = match self.buckets.get(cell) {
+				Some(bucket) => bucket,
+				None => continue,
+			};
+			for i in 0..bucket.len() {
+				let distance = self.tolerance.point_distance(point, &bucket[i].0);
+				if self.tolerance.within(distance) { return (Some((*cell, i)), distance) }
+				if distance < nearest { nearest = distance; }
+			}
 		}
+		return (None, nearest);
+	}
+	// a copy of this grid containing only points repeated at least "count" times
+	pub fn filter_by_count (&self, count: u64) -> GridVec {
+		let mut tree: GridVec = make_tracked_grid(self.provenance);
+		tree.round = self.round;
+		tree.tolerance = self.tolerance;
+		for (cell, bucket) in self.buckets.iter() {
+			for k in 0..bucket.len() {
+				if bucket[k].1 < count { continue }
+				tree.buckets.entry(*cell).or_insert_with(Vec::new).push(bucket[k]);
+				if self.provenance.tracking() {
+					let origins = self.origins[cell][k].clone();
+					tree.origins.entry(*cell).or_insert_with(Vec::new).push(origins);
+				}
+			}
+		}
+		return tree;
 	}
-	return GridVec { buckets };
 }
 
-impl GridVec {
-	// pub fn push (&mut self, point: Vector, axiom: u8) {
-	pub fn push (&mut self, point: Vector) {
-		let idx = point_to_index(point);
-		self.buckets[idx.0][idx.1].push((point, 1));
+impl PointIndex for GridVec {
+	fn make_empty (&self) -> GridVec {
+		let mut grid = make_tracked_grid(self.provenance);
+		grid.round = self.round;
+		grid.tolerance = self.tolerance;
+		return grid;
+	}
+	fn provenance (&self) -> Provenance { self.provenance }
+	fn round (&self) -> u8 { self.round }
+	fn set_round (&mut self, round: u8) { self.round = round; }
+	fn tolerance (&self) -> Tolerance { self.tolerance }
+	fn set_tolerance (&mut self, tolerance: Tolerance) {
+		let points = self.drain();
+		self.tolerance = tolerance;
+		refile_points(self, points);
+	}
+	fn edges (&self) -> EdgeCount { self.edges }
+	fn add_edges (&mut self, edges: &EdgeCount) { self.edges.add(edges); }
+	fn push_counted (&mut self, point: CountPoint, origins: &Vec<Origin>) {
+		let idx = point_to_index(point.0, self.tolerance.point_cells());
+		self.buckets.entry(idx).or_insert_with(Vec::new).push(point);
+		if self.provenance.tracking() {
+			let kept = self.provenance.keep(origins);
+			self.origins.entry(idx).or_insert_with(Vec::new).push(kept);
+		}
+	}
+	fn increment_nearest_by (&mut self, point: &Vector, count: u64, origins: &Vec<Origin>) -> (bool, f64) {
+		let (found, distance) = self.find(point);
+		let (cell, i) = match found {
+			Some(found) => found,
+			None => return (false, distance),
+		};
+		self.buckets.get_mut(&cell).unwrap()[i].1 += count;
+		if self.provenance.tracking() {
+			self.provenance.repeat(&mut self.origins.get_mut(&cell).unwrap()[i], origins);
+		}
+		return (true, distance);
+	}
+	fn find_origins (&self, point: &Vector) -> Option<&Vec<Origin>> {
+		let (cell, i) = self.find(point).0?;
+		return if self.provenance.tracking() { Some(&self.origins[&cell][i]) }
+			else { Some(&EMPTY_ORIGINS) };
 	}
-	// return true if match found. false if no match
-	pub fn increment_match (&mut self, point: &Vector) -> bool {
-		let idx = point_to_index(*point);
-		let bucket = &mut self.buckets[idx.0][idx.1];
-		for i in 0..bucket.len() {
-			if point.equivalent(bucket[i].0) {
-				bucket[i].1 += 1;
-				return true;
+	fn drain (&mut self) -> Vec<(CountPoint, Vec<Origin>)> {
+		let buckets = std::mem::replace(&mut self.buckets, BTreeMap::new());
+		let mut origins = std::mem::replace(&mut self.origins, BTreeMap::new());
+		let mut list: Vec<(CountPoint, Vec<Origin>)> = Vec::new();
+		for (cell, bucket) in buckets {
+			let mut cell_origins = origins.remove(&cell).unwrap_or(Vec::new()).into_iter();
+			for k in 0..bucket.len() {
+				list.push((bucket[k], cell_origins.next().unwrap_or(Vec::new())));
 			}
 		}
-		return false;
+		return list;
 	}
-	pub fn merge (&mut self, t: &mut GridVec) {
-		for i in 0..self.buckets.len() {
-			for j in 0..self.buckets[i].len() {
-				self.buckets[i][j].append(&mut t.buckets[i][j]);
+	fn merge (&mut self, t: &mut GridVec) {
+		self.edges.add(&t.edges);
+		t.edges = EdgeCount::default();
+		if t.tolerance != self.tolerance {
+			// the cells don't line up, file the points one at a time
+			let cells = self.tolerance.point_cells();
+			for (point, origins) in t.drain() {
+				let idx = point_to_index(point.0, cells);
+				self.buckets.entry(idx).or_insert_with(Vec::new).push(point);
+				if self.provenance.tracking() {
+					self.origins.entry(idx).or_insert_with(Vec::new).push(origins);
+				}
 			}
+			return;
+		}
+		let buckets = std::mem::replace(&mut t.buckets, BTreeMap::new());
+		let mut origins = std::mem::replace(&mut t.origins, BTreeMap::new());
+		for (cell, mut bucket) in buckets {
+			if self.provenance.tracking() {
+				// an untracked grid's points join with no origins
+				let mut cell_origins = origins.remove(&cell).unwrap_or(Vec::new());
+				while cell_origins.len() < bucket.len() { cell_origins.push(Vec::new()); }
+				self.origins.entry(cell).or_insert_with(Vec::new).append(&mut cell_origins);
+			}
+			self.buckets.entry(cell).or_insert_with(Vec::new).append(&mut bucket);
 		}
 	}
-	pub fn flatten (&self) -> Vec<CountPoint> {
+	fn flatten_filter (&self, count: u64) -> Vec<CountPoint> {
 		let mut list: Vec<CountPoint> = Vec::new();
-		for i in 0..self.buckets.len() {
-			for j in 0..self.buckets[i].len() {
-				for k in 0..self.buckets[i][j].len() {
-					list.push(self.buckets[i][j][k]);
+		for bucket in self.buckets.values() {
+			for k in 0..bucket.len() {
+				if bucket[k].1 >= count {
+					list.push(bucket[k]);
 				}
 			}
 		}
 		return list;
 	}
-	// pub fn flatten_filter (&self, count: u64) -> Vec<&CountPoint> {
-	// 	let mut list: Vec<&CountPoint> = Vec::new();
-	// 	for i in 0..self.buckets.len() {
-	// 		for j in 0..self.buckets[i].len() {
-	// 			for k in 0..self.buckets[i][j].len() {
-	// 				if self.buckets[i][j][k].1 >= count {
-	// 					list.push(&self.buckets[i][j][k]);
-	// 				}
-	// 			}
-	// 		}
-	// 	}
-	// 	return list;
-	// }
-
-	pub fn len (&self) -> usize {
-		let mut count: usize = 0;
-		for i in 0..self.buckets.len() {
-			for j in 0..self.buckets[i].len() {
-				count += self.buckets[i][j].len();
-			}
+	fn len (&self) -> usize {
+		return self.buckets.values().map(|bucket| bucket.len()).sum();
+	}
+}
+
+static EMPTY_ORIGINS: Vec<Origin> = Vec::new();
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// the grid has 1000 cells per unit. these build values on either side of
+	// a cell edge, one floating point step apart, so they are always equivalent
+
+	fn below (value: f64) -> f64 { f64::from_bits(value.to_bits() - 1) }
+
+	fn above (value: f64) -> f64 { f64::from_bits(value.to_bits() + 1) }
+
+	#[test]
+	fn points_across_a_cell_edge () {
+		let mut grid: GridVec = make_grid();
+		for i in 1..1000 {
+			let x = i as f64 / 1000.0;
+			grid.push(Vector { x: below(x), y: 0.2505 });
+			assert!(grid.increment_match(&Vector { x: x, y: 0.2505 }));
+			assert!(grid.increment_match(&Vector { x: above(x), y: 0.2505 }));
+			grid.push(Vector { x: 0.7505, y: above(x) });
+			assert!(grid.increment_match(&Vector { x: 0.7505, y: below(x) }));
 		}
-		return count;
+		let points = grid.flatten();
+		assert_eq!(points.len(), 2 * 999);
+		assert!(points.iter().all(|el| el.1 == 3 || el.1 == 2));
+		assert_eq!(points.iter().map(|el| el.1).sum::<u64>(), 5 * 999);
+	}
+
+	#[test]
+	fn points_across_a_cell_corner () {
+		let mut grid: GridVec = make_grid();
+		let corner = 0.5;
+		grid.push(Vector { x: below(corner), y: below(corner) });
+		assert!(grid.increment_match(&Vector { x: above(corner), y: below(corner) }));
+		assert!(grid.increment_match(&Vector { x: below(corner), y: above(corner) }));
+		assert!(grid.increment_match(&Vector { x: above(corner), y: above(corner) }));
+		assert_eq!(grid.len(), 1);
+		assert_eq!(grid.flatten()[0].1, 4);
+	}
+
+	#[test]
+	fn points_across_zero () {
+		let mut grid: GridVec = make_grid();
+		grid.push(Vector { x: 0.0, y: 0.0 });
+		assert!(grid.increment_match(&Vector { x: -1.0e-300, y: -1.0e-300 }));
+		assert!(grid.increment_match(&Vector { x: -0.0, y: 1.0e-300 }));
+		assert_eq!(grid.len(), 1);
+	}
+
+	#[test]
+	fn distinct_neighbors_are_kept () {
+		let mut grid: GridVec = make_grid();
+		grid.push(Vector { x: below(0.5), y: 0.5 });
+		assert!(!grid.increment_match(&Vector { x: 0.5 + 1.0e-4, y: 0.5 }));
 	}
-	// pub fn filter_by_count (&self, count: u64) -> GridVec {
-	// 	let mut tree: GridVec = make_grid();
-	// 	for i in 0..self.buckets.len() {
-	// 		for j in 0..self.buckets[i].len() {
-	// 			for k in 0..self.buckets[i][j].len() {
-	// 				if self.buckets[i][j][k].1 >= count {
-	// 					tree.buckets[i][j].push(self.buckets[i][j][k]);
-	// 				}
-	// 			}
-	// 		}
-	// 	}
-	// 	return tree;
-	// }
 }
@@ -0,0 +1,186 @@
+use rabbit_ear as ear;
+use self::ear::Vector;
+use self::ear::Line;
+use origami::CountPoint;
+use origami::CountLine;
+use origami::Origin;
+use origami::Provenance;
+use origami::Tolerance;
+use origami::EdgeCount;
+
+// the structures which store points and lines and find repeats of them.
+// the axioms, make_intersections and make_round work with any of them, the
+// choice only changes speed (and the order things are listed in).
+//
+// every entry has a count, a round (and lines an axiom), and when
+// provenance is tracked, the origins of its constructions. "round" is the
+// round given to entries made with push, which are initial geometry when
+// provenance is tracked. push never checks for a match,
+// check with increment_match first. entries are the same if they're closer
+// than the index's tolerance. each construction counted with add_point or
+// add_line, and each entry refiled, is tallied once in edges
+
+pub trait PointIndex {
+	// an empty index of the same kind, with the same provenance, round and
+	// tolerance. its edges start at zero
+	fn make_empty (&self) -> Self where Self: Sized;
+	fn provenance (&self) -> Provenance;
+	fn round (&self) -> u8;
+	fn set_round (&mut self, round: u8);
+	fn tolerance (&self) -> Tolerance;
+	// file every point again in cells sized for this tolerance, merging the
+	// ones it makes the same
+	fn set_tolerance (&mut self, tolerance: Tolerance);
+	fn edges (&self) -> EdgeCount;
+	fn add_edges (&mut self, edges: &EdgeCount);
+	// push an already counted point, keeping its count, round and origins
+	// (as many as the provenance keeps, none is none)
+	fn push_counted (&mut self, point: CountPoint, origins: &Vec<Origin>);
+	fn push (&mut self, point: Vector) {
+		let (round, origins) = (self.round(), self.provenance().start());
+		self.push_counted((point, 1, round), &origins);
+	}
+	// if a match is found, its count increases by "count", the origins are
+	// kept as the provenance asks, and returns true. also returns how far the
+	// match is, or without one, how far the nearest point searched is
+	// (infinite if none was near)
+	fn increment_nearest_by (&mut self, point: &Vector, count: u64, origins: &Vec<Origin>) -> (bool, f64);
+	fn increment_match_by (&mut self, point: &Vector, count: u64, origins: &Vec<Origin>) -> bool {
+		return self.increment_nearest_by(point, count, origins).0;
+	}
+	fn increment_match (&mut self, point: &Vector) -> bool {
+		return self.increment_match_by(point, 1, &vec![]);
+	}
+	// the constructions of a point, None if the point isn't in the index.
+	// empty if provenance isn't tracked
+	fn find_origins (&self, point: &Vector) -> Option<&Vec<Origin>>;
+	// every point with its origins, in the order of flatten, emptying the index
+	fn drain (&mut self) -> Vec<(CountPoint, Vec<Origin>)>;
+	// move every point of the other index into this one, without checking
+	// for matches, and add its edges. an untracked index's points join with
+	// no origins
+	fn merge (&mut self, other: &mut Self) where Self: Sized;
+	// only the points which have been repeated at least "count" times
+	fn flatten_filter (&self, count: u64) -> Vec<CountPoint>;
+	fn flatten (&self) -> Vec<CountPoint> { self.flatten_filter(0) }
+	fn len (&self) -> usize;
+}
+
+pub trait LineIndex {
+	// an empty index of the same kind, with the same provenance, round and
+	// tolerance. its edges start at zero
+	fn make_empty (&self) -> Self where Self: Sized;
+	fn provenance (&self) -> Provenance;
+	fn round (&self) -> u8;
+	fn set_round (&mut self, round: u8);
+	fn tolerance (&self) -> Tolerance;
+	// file every line again in cells sized for this tolerance, merging the
+	// ones it makes the same
+	fn set_tolerance (&mut self, tolerance: Tolerance);
+	fn edges (&self) -> EdgeCount;
+	fn add_edges (&mut self, edges: &EdgeCount);
+	// push an already counted line, keeping its count, axiom, round and
+	// origins (as many as the provenance keeps, none is none)
+	fn push_counted (&mut self, line: CountLine, origins: &Vec<Origin>);
+	fn push (&mut self, line: Line, axiom: u8) {
+		let (round, origins) = (self.round(), self.provenance().start());
+		self.push_counted((line, 1, axiom, round), &origins);
+	}
+	// if a match is found, its count increases by "count", the origins are
+	// kept as the provenance asks, and returns true. also returns how far the
+	// match is, or without one, how far the nearest line searched is
+	// (infinite if none was near)
+	fn increment_nearest_by (&mut self, line: &Line, count: u64, origins: &Vec<Origin>) -> (bool, f64);
+	fn increment_match_by (&mut self, line: &Line, count: u64, origins: &Vec<Origin>) -> bool {
+		return self.increment_nearest_by(line, count, origins).0;
+	}
+	fn increment_match (&mut self, line: &Line) -> bool {
+		return self.increment_match_by(line, 1, &vec![]);
+	}
+	// the constructions of a line, None if the line isn't in the index.
+	// empty if provenance isn't tracked
+	fn find_origins (&self, line: &Line) -> Option<&Vec<Origin>>;
+	// every line with its origins, in the order of flatten, emptying the index
+	fn drain (&mut self) -> Vec<(CountLine, Vec<Origin>)>;
+	// move every line of the other index into this one, without checking
+	// for matches, and add its edges. an untracked index's lines join with
+	// no origins
+	fn merge (&mut self, other: &mut Self) where Self: Sized;
+	// only the lines which have been repeated at least "count" times
+	fn flatten_filter (&self, count: u64) -> Vec<CountLine>;
+	fn flatten (&self) -> Vec<CountLine> { self.flatten_filter(0) }
+	fn len (&self) -> usize;
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PointIndexKind {
+	Grid,
+	QuadTree,
+}
+
+pub fn parse_point_index (string: &str) -> Option<PointIndexKind> {
+	match string {
+		"grid" => Some(PointIndexKind::Grid),
+		"quadtree" => Some(PointIndexKind::QuadTree),
+		_ => None,
+	}
+}
+
+// every point of one index pushed into another, with counts, rounds and origins
+pub fn copy_points<A: PointIndex, B: PointIndex> (from: &A, to: &mut B) {
+	let none = Vec::new();
+	for point in from.flatten() {
+		let origins = from.find_origins(&point.0).unwrap_or(&none);
+		to.push_counted(point, origins);
+	}
+}
+
+fn tally_edges (tolerance: &Tolerance, nearest: f64) -> EdgeCount {
+	let mut edges = EdgeCount::default();
+	edges.tally(tolerance, nearest);
+	return edges;
+}
+
+// count one construction: a repeat of an old point, else of a new one, else
+// a new point. it's tallied once, in the new index's edges, by where it
+// ended up: the match, or the nearest point it missed in either
+pub fn add_point<P: PointIndex> (old: &mut P, new: &mut P, point: CountPoint, origins: &Vec<Origin>) {
+	let tolerance = new.tolerance();
+	let (found, old_nearest) = old.increment_nearest_by(&point.0, point.1, origins);
+	if found { new.add_edges(&tally_edges(&tolerance, old_nearest)); return }
+	let (found, new_nearest) = new.increment_nearest_by(&point.0, point.1, origins);
+	if found { new.add_edges(&tally_edges(&tolerance, new_nearest)); return }
+	new.add_edges(&tally_edges(&tolerance, old_nearest.min(new_nearest)));
+	new.push_counted(point, origins);
+}
+
+pub fn add_line<L: LineIndex> (old: &mut L, new: &mut L, line: CountLine, origins: &Vec<Origin>) {
+	let tolerance = new.tolerance();
+	let (found, old_nearest) = old.increment_nearest_by(&line.0, line.1, origins);
+	if found { new.add_edges(&tally_edges(&tolerance, old_nearest)); return }
+	let (found, new_nearest) = new.increment_nearest_by(&line.0, line.1, origins);
+	if found { new.add_edges(&tally_edges(&tolerance, new_nearest)); return }
+	new.add_edges(&tally_edges(&tolerance, old_nearest.min(new_nearest)));
+	new.push_counted(line, origins);
+}
+
+// push drained points back into an index, counting the ones which match
+pub fn refile_points<P: PointIndex> (index: &mut P, points: Vec<(CountPoint, Vec<Origin>)>) {
+	let tolerance = index.tolerance();
+	for (point, origins) in points {
+		let (found, nearest) = index.increment_nearest_by(&point.0, point.1, &origins);
+		index.add_edges(&tally_edges(&tolerance, nearest));
+		if found { continue }
+		index.push_counted(point, &origins);
+	}
+}
+
+pub fn refile_lines<L: LineIndex> (index: &mut L, lines: Vec<(CountLine, Vec<Origin>)>) {
+	let tolerance = index.tolerance();
+	for (line, origins) in lines {
+		let (found, nearest) = index.increment_nearest_by(&line.0, line.1, &origins);
+		index.add_edges(&tally_edges(&tolerance, nearest));
+		if found { continue }
+		index.push_counted(line, &origins);
+	}
+}
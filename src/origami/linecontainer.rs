@@ -1,30 +1,99 @@
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
 use rabbit_ear as ear;
 use self::ear::Line;
 use origami::CountLine;
+use origami::Origin;
+use origami::Provenance;
+use origami::LineIndex;
+use origami::Tolerance;
+use origami::EdgeCount;
+use origami::tolerance::default_tolerance;
+use origami::index::refile_lines;
 
-const BUCKET: usize = 10000;
-const BUCKET_F: f64 = 10000.0;
+// (angle, distance)
+type Cell = (i64, i64);
 
 // fn binary_search (&Vec<Line>
 
+// a sparse grid of cells over the angle of a line's normal and its distance
+// from the origin. only cells containing lines are allocated, and they are
+// kept in order so flatten always lists the lines in the same order.
+// when provenance is tracked, "origins" mirrors "buckets": origins[c][k] are
+// the constructions of the line buckets[c][k]. otherwise origins are empty.
+// "round" is the round given to lines made with push.
+// the cells per radian and per unit distance come from the tolerance
 pub struct LineContainer {
-	pub buckets: Vec<Vec<CountLine>>
+	pub buckets: BTreeMap<Cell, Vec<CountLine>>,
+	pub origins: BTreeMap<Cell, Vec<Vec<Origin>>>,
+	pub provenance: Provenance,
+	pub round: u8,
+	pub tolerance: Tolerance,
+	pub edges: EdgeCount
 }
 
-fn point_to_index (line: &Line) -> usize {
-	let mut i: usize = (line.d.abs() /2_f64.sqrt() * BUCKET_F).floor() as usize;
-	// println!("p_2_ln {} {}", i, line.d);
-	if i >= BUCKET { i = 9999 }
-	return i;
+// (u, d) and (-u, -d) are the same line. the one with its normal's angle in
+// [0, pi) is used, as (angle, d). d can be negative
+fn polar (line: &Line) -> (f64, f64) {
+	let angle = line.u.y.atan2(line.u.x);
+	if angle < 0.0 {
+		// a tiny negative angle plus pi rounds to pi, the normal is at 0
+		if angle + PI >= PI { return (0.0, line.d) }
+		return (angle + PI, -line.d);
+	}
+	// the normal (-1, 0) is at pi, which is (1, 0) at 0
+	if angle >= PI { return (angle - PI, -line.d) }
+	return (angle, line.d);
+}
+
+// the cell of a normal angle and distance. angles past either end of [0, pi)
+// wrap around to the other end, and the line's distance changes sign
+fn polar_to_index (angle: f64, d: f64, tolerance: &Tolerance) -> Cell {
+	let (angle, d) = if angle < 0.0 { (angle + PI, -d) }
+		else if angle >= PI { (angle - PI, -d) }
+		else { (angle, d) };
+	let i = (angle * tolerance.angle_cells()).floor() as i64;
+	let j = (d * tolerance.distance_cells()).floor() as i64;
+	return (i, j);
+}
+
+fn point_to_index (line: &Line, tolerance: &Tolerance) -> Cell {
+	let (angle, d) = polar(line);
+	return polar_to_index(angle, d, tolerance);
+}
+
+// the cell a line is in, and the three cells next to it on the sides the
+// line is closest to, across the wrap around of the angle if needed. any
+// line less than half a cell away in angle and distance is in one of these
+fn nearby_cells (line: &Line, tolerance: &Tolerance) -> [Cell; 4] {
+	let (angle_cells, distance_cells) = (tolerance.angle_cells(), tolerance.distance_cells());
+	let (angle, d) = polar(line);
+	let (i, j) = polar_to_index(angle, d, tolerance);
+	let x = angle * angle_cells - i as f64;
+	let y = d * distance_cells - j as f64;
+	let da = if x < 0.5 { -0.5 } else { 0.5 } / angle_cells;
+	let dd = if y < 0.5 { -0.5 } else { 0.5 } / distance_cells;
+	return [
+		(i, j),
+		polar_to_index(angle + da, d, tolerance),
+		polar_to_index(angle, d + dd, tolerance),
+		polar_to_index(angle + da, d + dd, tolerance),
+	];
 }
 
 pub fn make_line_container () -> LineContainer {
-	let mut buckets: Vec<Vec<CountLine>> = Vec::new();
-	for _i in 0..BUCKET {
-		let row: Vec<CountLine> = Vec::new();
-		buckets.push(row);
-	}
-	return LineContainer { buckets };
+	return make_tracked_line_container(Provenance::None);
+}
+
+pub fn make_tracked_line_container (provenance: Provenance) -> LineContainer {
+	return LineContainer {
+		buckets: BTreeMap::new(),
+		origins: BTreeMap::new(),
+		provenance,
+		round: 0,
+		tolerance: default_tolerance(),
+		edges: EdgeCount::default()
+	};
 }
 
 // fn duplicate_line_check (line: &Line, lines: &mut Vec<CountLine>) -> bool {
@@ -38,66 +107,230 @@ pub fn make_line_container () -> LineContainer {
 // }
 
 impl LineContainer {
-	pub fn push (&mut self, line: &Line, axiom: u8) {
-		let idx = point_to_index(line);
-		self.buckets[idx].push((*line, 1, axiom));
-	}
-	// return true if match found. false if no match
-	pub fn increment_match (&mut self, line: &Line) -> bool {
-		let idx = point_to_index(line);
-		let bucket = &mut self.buckets[idx];
-		for i in 0..bucket.len() {
-			if line.equivalent(bucket[i].0) {
-				bucket[i].1 += 1;
-				return true;
+	// the cell and index of the first line equivalent to this one, and how
+	// far it is. without a match, how far the nearest line searched is
+	fn find (&self, line: &Line) -> (Option<(Cell, usize)>, f64) {
+		let mut nearest = std::f64::INFINITY;
+		for cell in nearby_cells(line, &self.tolerance).iter() {
+			let bucket = match self.buckets.get(cell) {
+				Some(bucket) => bucket,
+				None => continue,
+			};
+			for i in 0..bucket.len() {
+				let distance = self.tolerance.line_distance(line, &bucket[i].0);
+				if self.tolerance.within(distance) { return (Some((*cell, i)), distance) }
+				if distance < nearest { nearest = distance; }
 			}
 		}
-		return false;
+		return (None, nearest);
 	}
-	pub fn merge (&mut self, t: &mut LineContainer) {
-		for i in 0..self.buckets.len() {
-			self.buckets[i].append(&mut t.buckets[i]);
+	// a copy of this container with only lines repeated at least "count" times
+	pub fn filter_by_count (&self, count: u64) -> LineContainer {
+		let mut container: LineContainer = make_tracked_line_container(self.provenance);
+		container.round = self.round;
+		container.tolerance = self.tolerance;
+		for (cell, bucket) in self.buckets.iter() {
+			for k in 0..bucket.len() {
+				if bucket[k].1 < count { continue }
+				container.buckets.entry(*cell).or_insert_with(Vec::new).push(bucket[k]);
+				if self.provenance.tracking() {
+					let origins = self.origins[cell][k].clone();
+					container.origins.entry(*cell).or_insert_with(Vec::new).push(origins);
+				}
+			}
 		}
+		return container;
 	}
-	pub fn flatten (&self) -> Vec<CountLine> {
-		let mut list: Vec<CountLine> = Vec::new();
-		for i in 0..self.buckets.len() {
-			for j in 0..self.buckets[i].len() {
-				list.push(self.buckets[i][j]);
+}
+
+impl LineIndex for LineContainer {
+	fn make_empty (&self) -> LineContainer {
+		let mut container = make_tracked_line_container(self.provenance);
+		container.round = self.round;
+		container.tolerance = self.tolerance;
+		return container;
+	}
+	fn provenance (&self) -> Provenance { self.provenance }
+	fn round (&self) -> u8 { self.round }
+	fn set_round (&mut self, round: u8) { self.round = round; }
+	fn tolerance (&self) -> Tolerance { self.tolerance }
+	fn set_tolerance (&mut self, tolerance: Tolerance) {
+		let lines = self.drain();
+		self.tolerance = tolerance;
+		refile_lines(self, lines);
+	}
+	fn edges (&self) -> EdgeCount { self.edges }
+	fn add_edges (&mut self, edges: &EdgeCount) { self.edges.add(edges); }
+	fn push_counted (&mut self, line: CountLine, origins: &Vec<Origin>) {
+		let idx = point_to_index(&line.0, &self.tolerance);
+		self.buckets.entry(idx).or_insert_with(Vec::new).push(line);
+		if self.provenance.tracking() {
+			let kept = self.provenance.keep(origins);
+			self.origins.entry(idx).or_insert_with(Vec::new).push(kept);
+		}
+	}
+	fn increment_nearest_by (&mut self, line: &Line, count: u64, origins: &Vec<Origin>) -> (bool, f64) {
+		let (found, distance) = self.find(line);
+		let (cell, i) = match found {
+			Some(found) => found,
+			None => return (false, distance),
+		};
+		self.buckets.get_mut(&cell).unwrap()[i].1 += count;
+		if self.provenance.tracking() {
+			self.provenance.repeat(&mut self.origins.get_mut(&cell).unwrap()[i], origins);
+		}
+		return (true, distance);
+	}
+	fn find_origins (&self, line: &Line) -> Option<&Vec<Origin>> {
+		let (cell, i) = self.find(line).0?;
+		return if self.provenance.tracking() { Some(&self.origins[&cell][i]) }
+			else { Some(&EMPTY_ORIGINS) };
+	}
+	fn drain (&mut self) -> Vec<(CountLine, Vec<Origin>)> {
+		let buckets = std::mem::replace(&mut self.buckets, BTreeMap::new());
+		let mut origins = std::mem::replace(&mut self.origins, BTreeMap::new());
+		let mut list: Vec<(CountLine, Vec<Origin>)> = Vec::new();
+		for (cell, bucket) in buckets {
+			let mut cell_origins = origins.remove(&cell).unwrap_or(Vec::new()).into_iter();
+			for k in 0..bucket.len() {
+				list.push((bucket[k], cell_origins.next().unwrap_or(Vec::new())));
 			}
 		}
 		return list;
 	}
-	// pub fn flatten_filter (&self, count: u64) -> Vec<CountLine> {
-	// 	let mut list: Vec<CountLine> = Vec::new();
-	// 	for i in 0..self.buckets.len() {
-	// 		for j in 0..self.buckets[i].len() {
-	// 			if self.buckets[i][j].1 >= count {
-	// 				list.push(self.buckets[i][j]);
-	// 			}
-	// 		}
-	// 	}
-	// 	return list;
-	// }
-	pub fn len (&self) -> usize {
-		let mut count: usize = 0;
-		for i in 0..self.buckets.len() {
-			count += self.buckets[i].len();
+	fn merge (&mut self, t: &mut LineContainer) {
+		self.edges.add(&t.edges);
+		t.edges = EdgeCount::default();
+		if t.tolerance != self.tolerance {
+			// the cells don't line up, file the lines one at a time
+			for (line, origins) in t.drain() {
+				let idx = point_to_index(&line.0, &self.tolerance);
+				self.buckets.entry(idx).or_insert_with(Vec::new).push(line);
+				if self.provenance.tracking() {
+					self.origins.entry(idx).or_insert_with(Vec::new).push(origins);
+				}
+			}
+			return;
+		}
+		let buckets = std::mem::replace(&mut t.buckets, BTreeMap::new());
+		let mut origins = std::mem::replace(&mut t.origins, BTreeMap::new());
+		for (cell, mut bucket) in buckets {
+			if self.provenance.tracking() {
+				// an untracked container's lines join with no origins
+				let mut cell_origins = origins.remove(&cell).unwrap_or(Vec::new());
+				while cell_origins.len() < bucket.len() { cell_origins.push(Vec::new()); }
+				self.origins.entry(cell).or_insert_with(Vec::new).append(&mut cell_origins);
+			}
+			self.buckets.entry(cell).or_insert_with(Vec::new).append(&mut bucket);
 		}
-		return count;
-	}
-	// pub fn filter_by_count (&self, count: u64) -> GridVec {
-	// 	let mut tree: GridVec = make_tree();
-	// 	for i in 0..self.buckets.len() {
-	// 		for j in 0..self.buckets[i].len() {
-	// 			for k in 0..self.buckets[i][j].len() {
-	// 				if self.buckets[i][j][k].1 >= count {
-	// 					tree.buckets[i][j].push(self.buckets[i][j][k]);
-	// 				}
-	// 			}
-	// 		}
-	// 	}
-	// 	return tree;
-	// }
+	}
+	fn flatten_filter (&self, count: u64) -> Vec<CountLine> {
+		let mut list: Vec<CountLine> = Vec::new();
+		for bucket in self.buckets.values() {
+			for k in 0..bucket.len() {
+				if bucket[k].1 >= count {
+					list.push(bucket[k]);
+				}
+			}
+		}
+		return list;
+	}
+	fn len (&self) -> usize {
+		return self.buckets.values().map(|bucket| bucket.len()).sum();
+	}
 }
 
+static EMPTY_ORIGINS: Vec<Origin> = Vec::new();
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rabbit_ear::Vector;
+
+	// the container has 10000 cells across the normal angles [0, pi), and
+	// d / sqrt(2) is split into 10000 cells per unit. these build values on
+	// either side of a cell edge, one floating point step apart, so they are
+	// always equivalent
+
+	fn below (value: f64) -> f64 { f64::from_bits(value.to_bits() - 1) }
+
+	fn above (value: f64) -> f64 { f64::from_bits(value.to_bits() + 1) }
+
+	fn bucket_edge_d (bucket: usize) -> f64 { bucket as f64 * 2_f64.sqrt() / 10000.0 }
+
+	#[test]
+	fn lines_across_a_bucket_edge () {
+		let mut lines: LineContainer = make_line_container();
+		let u = Vector { x: 0.6, y: 0.8 };
+		for i in 1..10000 {
+			let d = bucket_edge_d(i);
+			lines.push(Line { u: u, d: below(d) }, 1);
+			assert!(lines.increment_match(&Line { u: u, d: d }));
+			assert!(lines.increment_match(&Line { u: u, d: above(d) }));
+		}
+		let flat = lines.flatten();
+		assert_eq!(flat.len(), 9999);
+		assert!(flat.iter().all(|el| el.1 == 3));
+	}
+
+	#[test]
+	fn lines_across_a_bucket_edge_opposite_normal () {
+		let mut lines: LineContainer = make_line_container();
+		let d = bucket_edge_d(5000);
+		lines.push(Line { u: Vector { x: 0.0, y: 1.0 }, d: below(d) }, 1);
+		assert!(lines.increment_match(&Line { u: Vector { x: 0.0, y: -1.0 }, d: -above(d) }));
+		assert_eq!(lines.len(), 1);
+		assert_eq!(lines.flatten()[0].1, 2);
+	}
+
+	#[test]
+	fn distinct_neighbors_are_kept () {
+		let mut lines: LineContainer = make_line_container();
+		let u = Vector { x: 1.0, y: 0.0 };
+		lines.push(Line { u: u, d: below(bucket_edge_d(10)) }, 1);
+		assert!(!lines.increment_match(&Line { u: u, d: bucket_edge_d(10) + 1.0e-5 }));
+	}
+
+	// a normal just past pi is a normal just past 0 with the opposite sign
+	#[test]
+	fn lines_across_the_angle_wrap () {
+		let mut lines: LineContainer = make_line_container();
+		lines.push(Line { u: Vector { x: 1.0, y: 1.0e-13 }, d: 0.3 }, 1);
+		assert!(lines.increment_match(&Line { u: Vector { x: 1.0, y: -1.0e-13 }, d: 0.3 }));
+		assert!(lines.increment_match(&Line { u: Vector { x: -1.0, y: 1.0e-13 }, d: -0.3 }));
+		assert!(lines.increment_match(&Line { u: Vector { x: -1.0, y: -1.0e-13 }, d: -0.3 }));
+		assert!(!lines.increment_match(&Line { u: Vector { x: -1.0, y: 1.0e-13 }, d: 0.3 }));
+		assert_eq!(lines.len(), 1);
+		assert_eq!(lines.flatten()[0].1, 4);
+	}
+
+	// a normal a rounding error below 0 is at 0, not at pi, whose cell is
+	// only searched from lines just below pi
+	#[test]
+	fn lines_a_rounding_error_below_the_wrap () {
+		let below_pi = Line { u: Vector { x: 1.0, y: -2.7755575615628914e-16 }, d: 0.4999999999999999 };
+		let below_zero = Line { u: Vector { x: 1.0, y: -6.409875621278546e-17 }, d: 0.5000000000000001 };
+		let mut lines: LineContainer = make_line_container();
+		lines.push(below_pi, 1);
+		assert!(lines.increment_match(&below_zero));
+		let mut lines: LineContainer = make_line_container();
+		lines.push(below_zero, 1);
+		assert!(lines.increment_match(&below_pi));
+	}
+
+	// lines through the same point, like the center, are spread out by angle
+	#[test]
+	fn lines_through_one_point () {
+		let mut lines: LineContainer = make_line_container();
+		for i in 0..1000 {
+			let angle = i as f64 * std::f64::consts::PI / 1000.0;
+			let u = Vector { x: angle.cos(), y: angle.sin() };
+			let line = Line { u: u, d: 0.5 * u.x + 0.5 * u.y };
+			assert!(!lines.increment_match(&line));
+			lines.push(line, 1);
+			assert!(lines.increment_match(&Line { u: Vector { x: -u.x, y: -u.y }, d: -line.d }));
+		}
+		assert_eq!(lines.len(), 1000);
+		assert!(lines.buckets.values().all(|bucket| bucket.len() == 1));
+	}
+}
@@ -1,5 +1,10 @@
+use std::thread;
+use std::sync::mpsc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use rabbit_ear as ear;
-use self::ear::Rect;
+use self::ear::Vector;
+use self::ear::Line;
 use self::ear::axioms::axiom1;
 use self::ear::axioms::axiom2;
 use self::ear::axioms::axiom3;
@@ -7,186 +12,270 @@ use self::ear::axioms::axiom4;
 use self::ear::axioms::axiom5;
 use self::ear::axioms::axiom6;
 use self::ear::axioms::axiom7;
-use super::GridVec;
-use super::make_grid;
-// use super::QuadTree;
-// use super::make_tree;
-use super::LineContainer;
+use super::PointIndex;
+use super::LineIndex;
+use super::Polygon;
+use super::EdgeCount;
+use super::provenance::make_origin;
+use super::index::add_point;
+use super::index::add_line;
 
 use super::CountPoint;
 use super::CountLine;
 
 const DEBUG: bool = true;
 const VERBOSE: bool = false;
+// each thread is handed this many chunks of the outer loop, on average
+const CHUNKS_PER_THREAD: usize = 8;
 
-pub fn make_intersections (
-	points: &mut GridVec, // already existing intersection points
+// 0 means one thread per available core
+pub fn thread_count (threads: usize) -> usize {
+	match threads {
+		0 => thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+		count => count,
+	}
+}
+
+// the solutions of a part of an axiom's loop, already counted against each
+// other in the order they were found
+struct Solutions<L> {
+	lines: L,
+}
+
+impl<L: LineIndex> Solutions<L> {
+	// points and parents are the axiom's inputs, kept if provenance is tracked
+	fn add (&mut self, solutions: &Vec<Line>, axiom: u8, points: &[Vector], parents: &[Line]) {
+		if solutions.len() == 0 { return }
+		let origins = make_origin(self.lines.provenance(), axiom, points, parents);
+		let tolerance = self.lines.tolerance();
+		let round = self.lines.round();
+		for i in 0..solutions.len() {
+			let (found, nearest) = self.lines.increment_nearest_by(&solutions[i], 1, &origins);
+			if !found {
+				self.lines.push_counted((solutions[i], 1, axiom, round), &origins);
+				continue;
+			}
+			let mut edges = EdgeCount::default();
+			edges.tally(&tolerance, nearest);
+			self.lines.add_edges(&edges);
+		}
+	}
+}
+
+// run the outer loop of an axiom (0..count) across "threads" threads (0 is
+// all cores). the loop is cut into chunks which threads claim one at a time,
+// and every chunk counts its own solutions in an empty copy of new_lines.
+// the finished chunks are merged in chunk order, as soon as the ones before
+// them are, against the old lines then the new lines. the repeats inside a
+// chunk were tallied when they were counted, the rest are tallied as they
+// merge, so each construction is tallied once. the chunks are merged in the
+// order the loop runs serially, so the lines, counts and origins are the same
+// with any number of threads
+fn solve_parallel<L, F> (
+	count: usize,
+	threads: usize,
+	old_lines: &mut L, // the previous round (check for duplicates only)
+	new_lines: &mut L, // the current round (check for duplicates only)
+	solve: F // solve one index of the outer loop, adding to the chunk's solutions
+) where L: LineIndex + Send + Sync, F: Fn(usize, &mut Solutions<L>) + Sync {
+	let threads = thread_count(threads);
+	let chunk_size = std::cmp::max(1, count / (threads * CHUNKS_PER_THREAD));
+	let chunk_count = (count + chunk_size - 1) / chunk_size;
+	let next_chunk = AtomicUsize::new(0);
+	let empty: L = new_lines.make_empty();
+	let (sender, receiver) = mpsc::channel::<(usize, L)>();
+	thread::scope(|scope| {
+		for _ in 0..std::cmp::min(threads, chunk_count) {
+			let (sender, next_chunk, empty, solve) = (sender.clone(), &next_chunk, &empty, &solve);
+			scope.spawn(move || {
+				loop {
+					let chunk = next_chunk.fetch_add(1, Ordering::Relaxed);
+					if chunk >= chunk_count { break }
+					let mut solutions = Solutions { lines: empty.make_empty() };
+					let end = std::cmp::min(count, (chunk + 1) * chunk_size);
+					for i in (chunk * chunk_size)..end { solve(i, &mut solutions); }
+					if sender.send((chunk, solutions.lines)).is_err() { break }
+				}
+			});
+		}
+		drop(sender);
+		// chunks which finished before the ones ahead of them wait here
+		let mut waiting: Vec<Option<L>> = (0..chunk_count).map(|_| None).collect();
+		let mut next_merge: usize = 0;
+		for (chunk, lines) in receiver {
+			waiting[chunk] = Some(lines);
+			while next_merge < chunk_count {
+				let mut lines = match waiting[next_merge].take() {
+					Some(lines) => lines,
+					None => break,
+				};
+				new_lines.add_edges(&lines.edges());
+				for (line, origins) in lines.drain() {
+					add_line(old_lines, new_lines, line, &origins);
+				}
+				next_merge += 1;
+			}
+		}
+	});
+}
+
+pub fn make_intersections<P: PointIndex> (
+	points: &mut P, // already existing intersection points
 	old_lines: &Vec<CountLine>, // all lines from previous rounds
 	new_lines: &Vec<CountLine>, // the newest set of lines
-	polygon: Rect
-) -> GridVec {
-	let mut round: GridVec = make_grid();
-	// concat new and old lines into one list "all_lines"
-	let mut all_lines: Vec<CountLine> = Vec::new();
-	for i in 0..new_lines.len() { all_lines.push(new_lines[i]) }
-	for i in 0..old_lines.len() { all_lines.push(old_lines[i]) }
-	// if this message prints, the for loop j in (i+1) will start beyond all_lines. needs fix
-	if old_lines.len() == 0 { println!("ATTN: make.rs, case not considered. need fix"); }
-	// get intersections points comparing two arrays: new_lines to old_lines
-	// this compares every new line to every new AND old line, but avoids
-	// old lines getting compared to themselves again (which already happened)
-	if new_lines.len() == 0 { return round }
-	// println!("starting loop");
+	polygon: &Polygon
+) -> P {
+	let mut round: P = points.make_empty();
+	// every new line crosses each other new line once, and every old line.
+	// the old lines were already crossed with each other in earlier rounds
 	for i in 0..new_lines.len() {
 		if DEBUG && VERBOSE { println!("{}/{} new points", i, new_lines.len()); }
-		// if DEBUG { println!("{}/{}: {} new points", i, new_lines.len(), round.len()); }
-		for j in (i + 1)..all_lines.len() {
-			let (success, point) = new_lines[i].0.intersect(all_lines[j].0);
-			if !success { continue }
-			if !polygon.contains(point) { continue }
-			if points.increment_match(&point) { continue }
-			if round.increment_match(&point) { continue }
-			// round.push(&point); // automatically makes tuple (point, 1)
-			round.push(point); // automatically makes tuple (point, 1)
-			// round.push((point, 1)); // automatically makes tuple (point, 1)
+		for j in (i + 1)..new_lines.len() {
+			add_intersection(points, &mut round, &new_lines[i], &new_lines[j], polygon);
+		}
+		for j in 0..old_lines.len() {
+			add_intersection(points, &mut round, &new_lines[i], &old_lines[j], polygon);
 		}
 	}
 	if DEBUG { println!("intersections done. {} new points this round", round.len()); }
 	return round;
 }
 
+// the point where a new line crosses another line, if it's on the paper.
+// the point first appears in the round of the new line
+fn add_intersection<P: PointIndex> (
+	points: &mut P,
+	round: &mut P,
+	new_line: &CountLine,
+	line: &CountLine,
+	polygon: &Polygon
+) {
+	let (success, point) = new_line.0.intersect(line.0);
+	if !success { return }
+	if !polygon.contains(point) { return }
+	let origins = make_origin(points.provenance(), 0, &[], &[new_line.0, line.0]);
+	add_point(points, round, (point, 1, new_line.3), &origins);
+}
+
 // these parameters are pointers, because all these methods are called
 // in sequence, preventing variable moving, allowing reuse
-pub fn make_axiom1 (
+pub fn make_axiom1<L: LineIndex + Send + Sync> (
 	points: &Vec<CountPoint>, // the previous round of points (build from this)
-	old_lines: &mut LineContainer, // the previous round of lines (build from this)
-	new_lines: &mut LineContainer, // the current round (check for duplicates only)
-	boundary: Rect
+	old_lines: &mut L, // the previous round of lines (build from this)
+	new_lines: &mut L, // the current round (check for duplicates only)
+	boundary: &Polygon,
+	threads: usize // 0 is all cores
 ) {
-	for i in 0..points.len() - 1 {
-		if DEBUG && VERBOSE { println!("{}/{}: {} axiom 1", i, points.len(), new_lines.len()); }
+	solve_parallel(points.len(), threads, old_lines, new_lines, |i, found| {
+		if DEBUG && VERBOSE { println!("{}/{}: axiom 1", i, points.len()); }
 		for j in (i + 1)..points.len() {
-			let solutions = axiom1(points[i].0, points[j].0, boundary);
-			for k in 0..solutions.len() {
-				if old_lines.increment_match(&solutions[k]) { continue }
-				if new_lines.increment_match(&solutions[k]) { continue }
-				new_lines.push(&solutions[k], 1);
-			}
+			let solutions = boundary.keep(axiom1(points[i].0, points[j].0, boundary.frame));
+			found.add(&solutions, 1, &[points[i].0, points[j].0], &[]);
 		}
-	}
+	});
 	if DEBUG { println!("axiom 1 done. {} lines this round", new_lines.len()); }
 }
 
-pub fn make_axiom2 (
+pub fn make_axiom2<L: LineIndex + Send + Sync> (
 	points: &Vec<CountPoint>, // the previous round of points (build from this)
-	old_lines: &mut LineContainer, // the previous round of lines (build from this)
-	new_lines: &mut LineContainer, // the current round (check for duplicates only)
-	boundary: Rect
+	old_lines: &mut L, // the previous round of lines (build from this)
+	new_lines: &mut L, // the current round (check for duplicates only)
+	boundary: &Polygon,
+	threads: usize // 0 is all cores
 ) {
-	for i in 0..points.len() - 1 {
-		if DEBUG && VERBOSE { println!("{}/{}: {} axiom 2", i, points.len(), new_lines.len()); }
+	solve_parallel(points.len(), threads, old_lines, new_lines, |i, found| {
+		if DEBUG && VERBOSE { println!("{}/{}: axiom 2", i, points.len()); }
 		for j in (i + 1)..points.len() {
-			let solutions = axiom2(points[i].0, points[j].0, boundary);
-			for k in 0..solutions.len() {
-				if old_lines.increment_match(&solutions[k]) { continue }
-				if new_lines.increment_match(&solutions[k]) { continue }
-				new_lines.push(&solutions[k], 2);
-			}
+			let solutions = boundary.keep(axiom2(points[i].0, points[j].0, boundary.frame));
+			found.add(&solutions, 2, &[points[i].0, points[j].0], &[]);
 		}
-	}
+	});
 	if DEBUG { println!("axiom 2 done. {} lines this round", new_lines.len()); }
 }
 
-pub fn make_axiom3 (
+pub fn make_axiom3<L: LineIndex + Send + Sync> (
 	_points: &Vec<CountPoint>, // the previous round of points (build from this)
 	lines: &Vec<CountLine>, // the previous round of lines as list (build from this)
-	old_lines: &mut LineContainer, // the previous round (check for duplicates only)
-	new_lines: &mut LineContainer, // the current round (check for duplicates only)
-	boundary: Rect
+	old_lines: &mut L, // the previous round (check for duplicates only)
+	new_lines: &mut L, // the current round (check for duplicates only)
+	boundary: &Polygon,
+	threads: usize // 0 is all cores
 ) {
-	for i in 0..lines.len() - 1 {
-		if DEBUG && VERBOSE { println!("{}/{}: {} axiom 3", i, lines.len(), new_lines.len()); }
+	solve_parallel(lines.len(), threads, old_lines, new_lines, |i, found| {
+		if DEBUG && VERBOSE { println!("{}/{}: axiom 3", i, lines.len()); }
 		for j in (i + 1)..lines.len() {
-			let solutions = axiom3(lines[i].0, lines[j].0, boundary);
-			for k in 0..solutions.len() {
-				if old_lines.increment_match(&solutions[k]) { continue }
-				if new_lines.increment_match(&solutions[k]) { continue }
-				new_lines.push(&solutions[k], 3);
-			}
+			let solutions = boundary.keep(axiom3(lines[i].0, lines[j].0, boundary.frame));
+			found.add(&solutions, 3, &[], &[lines[i].0, lines[j].0]);
 		}
-	}
+	});
 	if DEBUG { println!("axiom 3 done. {} lines this round", new_lines.len()); }
 }
 
-pub fn make_axiom4 (
+pub fn make_axiom4<L: LineIndex + Send + Sync> (
 	points: &Vec<CountPoint>, // the previous round of points (build from this)
 	lines: &Vec<CountLine>, // the previous round of lines as list (build from this)
-	old_lines: &mut LineContainer, // the previous round (check for duplicates only)
-	new_lines: &mut LineContainer, // the current round (check for duplicates only)
-	boundary: Rect
+	old_lines: &mut L, // the previous round (check for duplicates only)
+	new_lines: &mut L, // the current round (check for duplicates only)
+	boundary: &Polygon,
+	threads: usize // 0 is all cores
 ) {
-	for i in 0..points.len() {
-		if DEBUG && VERBOSE { println!("{}/{}: {} axiom 4", i, points.len(), new_lines.len()); }
+	solve_parallel(points.len(), threads, old_lines, new_lines, |i, found| {
+		if DEBUG && VERBOSE { println!("{}/{}: axiom 4", i, points.len()); }
 		for j in 0..lines.len() {
-			let solutions = axiom4(points[i].0, lines[j].0, boundary);
-			for k in 0..solutions.len() {
-				if old_lines.increment_match(&solutions[k]) { continue }
-				if new_lines.increment_match(&solutions[k]) { continue }
-				new_lines.push(&solutions[k], 4);
-			}
+			let solutions = boundary.keep(axiom4(points[i].0, lines[j].0, boundary.frame));
+			found.add(&solutions, 4, &[points[i].0], &[lines[j].0]);
 		}
-	}
+	});
 	if DEBUG { println!("axiom 4 done. {} lines this round", new_lines.len()); }
 }
 
-pub fn make_axiom5 (
+pub fn make_axiom5<L: LineIndex + Send + Sync> (
 	points: &Vec<CountPoint>, // the previous round of points (build from this)
 	lines: &Vec<CountLine>, // the previous round of lines as list (build from this)
-	old_lines: &mut LineContainer, // the previous round (check for duplicates only)
-	new_lines: &mut LineContainer, // the current round (check for duplicates only)
-	boundary: Rect
+	old_lines: &mut L, // the previous round (check for duplicates only)
+	new_lines: &mut L, // the current round (check for duplicates only)
+	boundary: &Polygon,
+	threads: usize // 0 is all cores
 ) {
-	for i in 0..points.len() {
+	solve_parallel(points.len(), threads, old_lines, new_lines, |i, found| {
 		for j in 0..points.len() {
 			if i == j { continue }
-			if DEBUG && VERBOSE { println!("{}/{}: {} axiom 5", i, points.len(), new_lines.len()); }
+			if DEBUG && VERBOSE { println!("{}/{}: axiom 5", i, points.len()); }
 			for k in 0..lines.len() {
-				let solutions = axiom5(points[i].0, points[j].0, lines[k].0, boundary);
-				for l in 0..solutions.len() {
-					if old_lines.increment_match(&solutions[l]) { continue }
-					if new_lines.increment_match(&solutions[l]) { continue }
-					new_lines.push(&solutions[l], 5);
-				}
+				// one point is folded onto the line, the fold passes through
+				// the other, which stays where it is. both have to stay on the paper
+				let solutions = boundary.keep_folds(
+					axiom5(points[i].0, points[j].0, lines[k].0, boundary.frame), &[points[i].0, points[j].0]);
+				found.add(&solutions, 5, &[points[i].0, points[j].0], &[lines[k].0]);
 			}
 		}
-	}
+	});
 	if DEBUG { println!("axiom 5 done. {} lines this round", new_lines.len()); }
 }
 
-pub fn make_axiom6 (
+pub fn make_axiom6<L: LineIndex + Send + Sync> (
 	points: &Vec<CountPoint>, // the previous round of points (build from this)
 	lines: &Vec<CountLine>, // the previous round of lines as list (build from this)
-	old_lines: &mut LineContainer, // the previous round (check for duplicates only)
-	new_lines: &mut LineContainer, // the current round (check for duplicates only)
-	boundary: Rect
+	old_lines: &mut L, // the previous round (check for duplicates only)
+	new_lines: &mut L, // the current round (check for duplicates only)
+	boundary: &Polygon,
+	threads: usize // 0 is all cores
 ) {
-	for i in 0..points.len() {
+	solve_parallel(points.len(), threads, old_lines, new_lines, |i, found| {
 		for j in 0..points.len() {
-			if DEBUG && VERBOSE { println!("{}/{}pts: ({}pt): {} axiom 6", i, points.len(), j, new_lines.len()); }
+			if DEBUG && VERBOSE { println!("{}/{}pts: ({}pt): axiom 6", i, points.len(), j); }
 			if i == j { continue }
 			for k in 0..lines.len() {
 				for l in 0..lines.len() {
 					if k == l { continue }
-					let solutions = axiom6(points[i].0, points[j].0, lines[k].0, lines[l].0, boundary);
-					for m in 0..solutions.len() {
-						if old_lines.increment_match(&solutions[m]) { continue }
-						if new_lines.increment_match(&solutions[m]) { continue }
-						new_lines.push(&solutions[m], 6);
-					}
+					let solutions = boundary.keep_folds(
+						axiom6(points[i].0, points[j].0, lines[k].0, lines[l].0, boundary.frame), &[points[i].0, points[j].0]);
+					found.add(&solutions, 6,
+						&[points[i].0, points[j].0], &[lines[k].0, lines[l].0]);
 				}
 			}
 		}
-	}
+	});
 	if DEBUG { println!("axiom 6 done. {} lines this round", new_lines.len()); }
 }
 
@@ -223,7 +312,7 @@ pub fn make_axiom6 (
 // 					if jj >= points.len() { jj = j }
 // 					if kk >= lines.len() { kk = k }
 // 					if ll >= lines.len() { ll = l }
-// 					let solutions = axiom6(points[ii].0, &points[jj].0, &lines[kk].0, &lines[ll].0, boundary);
+// 					let solutions = boundary.keep(axiom6(points[ii].0, &points[jj].0, &lines[kk].0, &lines[ll].0, boundary.frame));
 // 					for m in 0..solutions.len() {
 // 						if old_lines.increment_match(&solutions[m]) { continue }
 // 						if new_lines.increment_match(&solutions[m]) { continue }
@@ -235,26 +324,24 @@ pub fn make_axiom6 (
 // 	}
 // }
 
-pub fn make_axiom7 (
+pub fn make_axiom7<L: LineIndex + Send + Sync> (
 	points: &Vec<CountPoint>, // the previous round of points (build from this)
 	lines: &Vec<CountLine>, // the previous round of lines as list (build from this)
-	old_lines: &mut LineContainer, // the previous round (check for duplicates only)
-	new_lines: &mut LineContainer, // the current round (check for duplicates only)
-	boundary: Rect
+	old_lines: &mut L, // the previous round (check for duplicates only)
+	new_lines: &mut L, // the current round (check for duplicates only)
+	boundary: &Polygon,
+	threads: usize // 0 is all cores
 ) {
-	for i in 0..points.len() {
+	solve_parallel(points.len(), threads, old_lines, new_lines, |i, found| {
 		for j in 0..lines.len() {
-			if DEBUG && VERBOSE { println!("{}/{}: {} axiom 7", i, points.len(), new_lines.len()); }
+			if DEBUG && VERBOSE { println!("{}/{}: axiom 7", i, points.len()); }
 			for k in 0..lines.len() {
 				if j == k { continue }
-				let solutions = axiom7(points[i].0, lines[j].0, lines[k].0, boundary);
-				for l in 0..solutions.len() {
-					if old_lines.increment_match(&solutions[l]) { continue }
-					if new_lines.increment_match(&solutions[l]) { continue }
-					new_lines.push(&solutions[l], 7);
-				}
+				let solutions = boundary.keep_folds(
+					axiom7(points[i].0, lines[j].0, lines[k].0, boundary.frame), &[points[i].0]);
+				found.add(&solutions, 7, &[points[i].0], &[lines[j].0, lines[k].0]);
 			}
 		}
-	}
+	});
 	if DEBUG { println!("axiom 7 done. {} lines this round", new_lines.len()); }
 }
@@ -0,0 +1,89 @@
+// a round plan says which axioms run in a round, and the minimum number of
+// repeats a point or line needs to have to be used as an input to each axiom.
+// a minimum of 0 or 1 uses every point and line. the axioms are built on
+// "threads" threads, 0 is one per core.
+
+#[derive(Clone, Debug)]
+pub struct RoundPlan {
+	pub axioms: Vec<u8>,
+	pub intersections: bool,
+	// indexed by axiom - 1
+	pub min_points: [u64; 7],
+	pub min_lines: [u64; 7],
+	pub threads: usize,
+}
+
+pub fn make_round_plan () -> RoundPlan {
+	RoundPlan {
+		axioms: vec![1, 2, 3, 4, 5, 6, 7],
+		intersections: true,
+		min_points: [0; 7],
+		min_lines: [0; 7],
+		threads: 0,
+	}
+}
+
+fn filter_of (minimums: &[u64; 7], axiom: u8) -> u64 {
+	if axiom < 1 || axiom > 7 { return 0 }
+	return minimums[(axiom - 1) as usize];
+}
+
+impl RoundPlan {
+	// 0 for an axiom which doesn't exist, including 0, which set_filter
+	// takes to mean all axioms
+	pub fn min_points (&self, axiom: u8) -> u64 { filter_of(&self.min_points, axiom) }
+	pub fn min_lines (&self, axiom: u8) -> u64 { filter_of(&self.min_lines, axiom) }
+	// axiom 0 sets the minimum for all axioms
+	pub fn set_filter (&mut self, axiom: u8, points: u64, lines: u64) {
+		for i in 0..7 {
+			if axiom == 0 || axiom as usize == i + 1 {
+				self.min_points[i] = points;
+				self.min_lines[i] = lines;
+			}
+		}
+	}
+}
+
+// thresholds that have worked well in the past, by round.
+// (points, lines) for axioms 1-4, then for axioms 5, 6, 7
+fn preset_round (name: &str, round: usize) -> Option<[(u64, u64); 4]> {
+	match name {
+		"light" => Some(match round {
+			0..=1 => [(0, 0), (0, 0), (0, 0), (0, 0)],
+			2 => [(2, 0), (8, 8), (12, 12), (4, 4)],
+			3 => [(16, 6), (72, 64), (72, 64), (72, 64)],
+			_ => [(144, 12), (144, 144), (144, 144), (144, 144)],
+		}),
+		// good for making points. faster than "points-full", half a million points.
+		"points" => Some(match round {
+			0..=1 => [(0, 0), (0, 0), (0, 0), (0, 0)],
+			2 => [(24, 16), (36, 36), (52, 52), (36, 36)],
+			3 => [(72, 64), (72, 64), (72, 64), (72, 64)],
+			_ => [(144, 144), (144, 144), (144, 144), (144, 144)],
+		}),
+		// good for making points. still takes a while
+		"points-full" => Some(match round {
+			0..=1 => [(0, 0), (0, 0), (0, 0), (0, 0)],
+			2 => [(12, 8), (28, 28), (32, 32), (28, 28)],
+			3 => [(72, 64), (72, 64), (72, 64), (72, 64)],
+			_ => [(144, 144), (144, 144), (144, 144), (144, 144)],
+		}),
+		_ => None,
+	}
+}
+
+pub fn preset_names () -> [&'static str; 3] { ["light", "points", "points-full"] }
+
+// apply a named preset's thresholds to a round plan. false if the name is unknown
+pub fn apply_preset (plan: &mut RoundPlan, name: &str, round: usize) -> bool {
+	let thresholds = match preset_round(name, round) {
+		Some(thresholds) => thresholds,
+		None => return false,
+	};
+	for axiom in 1..=4 { plan.set_filter(axiom, thresholds[0].0, thresholds[0].1); }
+	for axiom in 5..=7 {
+		let (points, lines) = thresholds[(axiom - 4) as usize];
+		plan.set_filter(axiom, points, lines);
+	}
+	return true;
+}
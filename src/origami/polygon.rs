@@ -0,0 +1,208 @@
+use rabbit_ear as ear;
+use self::ear::Vector;
+use self::ear::Line;
+use self::ear::Segment;
+use self::ear::Rect;
+use self::ear::make_square;
+use self::ear::axioms::axiom1;
+
+const EPSILON: f64 = 1.0e-10;
+
+// a convex sheet of paper, vertices counter-clockwise. every shape is scaled
+// to fit inside the unit square with its bounding box at the origin, because
+// the axioms are given the unit square (the "frame") as their boundary, and
+// the point and line indices expect coordinates inside the unit square.
+// the axioms only check their solutions against the frame, keep and
+// keep_folds check them again against the paper.
+// "offset" and "scale" undo the fit, back to the units the shape was made in
+// (the paper's units), which is what people read and type coordinates in
+#[derive(Clone)]
+pub struct Polygon {
+	pub vertices: Vec<Vector>,
+	pub sides: Vec<Line>,
+	pub frame: Rect,
+	// the unit square itself, clip and contains are left to the frame
+	pub square: bool,
+	pub offset: Vector,
+	pub scale: f64,
+}
+
+fn cross (o: Vector, a: Vector, b: Vector) -> f64 {
+	(a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+// where a point lands when folded along a line
+fn reflect (line: &Line, point: Vector) -> Vector {
+	let distance = line.u.x * point.x + line.u.y * point.y - line.d;
+	Vector { x: point.x - 2.0 * distance * line.u.x, y: point.y - 2.0 * distance * line.u.y }
+}
+
+// trigonometry leaves vertices a rounding error away from the frame's edges
+fn snap (value: f64) -> f64 {
+	if value.abs() < EPSILON { return 0.0 }
+	if (value - 1.0).abs() < EPSILON { return 1.0 }
+	return value;
+}
+
+pub fn make_unit_square () -> Polygon {
+	let mut polygon = make_polygon(vec![
+		Vector { x: 0.0, y: 0.0 },
+		Vector { x: 1.0, y: 0.0 },
+		Vector { x: 1.0, y: 1.0 },
+		Vector { x: 0.0, y: 1.0 },
+	]).unwrap();
+	// the frame's sides, which the initial geometry has always used
+	polygon.sides = polygon.frame.sides.to_vec();
+	polygon.square = true;
+	return polygon;
+}
+
+// a rectangle with width / height = aspect. 2^0.5 is A4 (the silver rectangle)
+pub fn make_rectangle (aspect: f64) -> Option<Polygon> {
+	if !(aspect > 0.0) { return None }
+	if (aspect - 1.0).abs() < EPSILON { return Some(make_unit_square()) }
+	return make_polygon(vec![
+		Vector { x: 0.0, y: 0.0 },
+		Vector { x: aspect, y: 0.0 },
+		Vector { x: aspect, y: 1.0 },
+		Vector { x: 0.0, y: 1.0 },
+	]);
+}
+
+// a regular polygon with one side along the bottom
+pub fn make_regular_polygon (sides: usize) -> Option<Polygon> {
+	if sides < 3 { return None }
+	if sides == 4 { return Some(make_unit_square()) }
+	let angle = 2.0 * std::f64::consts::PI / sides as f64;
+	let start = -std::f64::consts::FRAC_PI_2 - angle / 2.0;
+	let vertices = (0..sides).map(|i| {
+		let a = start + angle * i as f64;
+		Vector { x: a.cos(), y: a.sin() }
+	}).collect();
+	return make_polygon(vertices);
+}
+
+// any convex polygon, scaled and moved to fit inside the unit square.
+// None if there are fewer than 3 vertices or the polygon isn't convex
+pub fn make_polygon (vertices: Vec<Vector>) -> Option<Polygon> {
+	if vertices.len() < 3 { return None }
+	let min_x = vertices.iter().fold(std::f64::INFINITY, |m, v| m.min(v.x));
+	let min_y = vertices.iter().fold(std::f64::INFINITY, |m, v| m.min(v.y));
+	let max_x = vertices.iter().fold(std::f64::NEG_INFINITY, |m, v| m.max(v.x));
+	let max_y = vertices.iter().fold(std::f64::NEG_INFINITY, |m, v| m.max(v.y));
+	let size = (max_x - min_x).max(max_y - min_y);
+	if !(size > EPSILON) { return None }
+	let mut vertices: Vec<Vector> = vertices.iter()
+		.map(|v| Vector { x: snap((v.x - min_x) / size), y: snap((v.y - min_y) / size) })
+		.collect();
+	// counter-clockwise, by the sign of the area
+	let mut area = 0.0;
+	for i in 0..vertices.len() {
+		let (a, b) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+		area += a.x * b.y - b.x * a.y;
+	}
+	if area < 0.0 { vertices.reverse(); }
+	for i in 0..vertices.len() {
+		let n = vertices.len();
+		if cross(vertices[i], vertices[(i + 1) % n], vertices[(i + 2) % n]) < -EPSILON {
+			return None;
+		}
+	}
+	let frame = make_square();
+	let n = vertices.len();
+	let sides: Vec<Line> = (0..n)
+		.flat_map(|i| axiom1(vertices[i], vertices[(i + 1) % n], frame))
+		.collect();
+	if sides.len() != n { return None }
+	let offset = Vector { x: min_x, y: min_y };
+	return Some(Polygon { vertices, sides, frame, square: false, offset, scale: size });
+}
+
+// "square", "a4", "triangle", "hexagon", "rectangle:RATIO", "regular:SIDES"
+pub fn parse_polygon (string: &str) -> Option<Polygon> {
+	let parts: Vec<&str> = string.splitn(2, ':').collect();
+	match (parts[0], parts.get(1)) {
+		("square", None) => Some(make_unit_square()),
+		("a4", None) => make_rectangle(1.0 / 2_f64.sqrt()),
+		("triangle", None) => make_regular_polygon(3),
+		("hexagon", None) => make_regular_polygon(6),
+		("rectangle", Some(ratio)) => ratio.parse::<f64>().ok().and_then(make_rectangle),
+		("regular", Some(sides)) => sides.parse::<usize>().ok().and_then(make_regular_polygon),
+		_ => None,
+	}
+}
+
+impl Polygon {
+	// (min, max) corners of the bounding box
+	pub fn bounds (&self) -> (Vector, Vector) {
+		let mut min = self.vertices[0];
+		let mut max = self.vertices[0];
+		for v in self.vertices.iter() {
+			min = Vector { x: min.x.min(v.x), y: min.y.min(v.y) };
+			max = Vector { x: max.x.max(v.x), y: max.y.max(v.y) };
+		}
+		return (min, max);
+	}
+	// points on the edge are inside
+	pub fn contains (&self, point: Vector) -> bool {
+		if self.square { return self.frame.contains(point) }
+		let n = self.vertices.len();
+		for i in 0..n {
+			if cross(self.vertices[i], self.vertices[(i + 1) % n], point) < -EPSILON {
+				return false;
+			}
+		}
+		return true;
+	}
+	// the part of a line inside the polygon. false if the line misses the
+	// polygon, or only touches it at one point
+	pub fn clip (&self, line: Line) -> (bool, Segment) {
+		if self.square { return self.frame.clip(line) }
+		let mut ends: Vec<Vector> = Vec::new();
+		for side in self.sides.iter() {
+			let (success, point) = line.intersect(*side);
+			if !success || !self.contains(point) { continue }
+			if ends.iter().any(|end| end.equivalent(point)) { continue }
+			ends.push(point);
+		}
+		if ends.len() < 2 {
+			let zero = Vector { x: 0.0, y: 0.0 };
+			return (false, Segment { a: zero, b: zero });
+		}
+		// a line through a vertex can find more than 2, keep the farthest pair
+		let mut pair = (ends[0], ends[1]);
+		let mut distance = 0.0;
+		for i in 0..ends.len() {
+			for j in (i + 1)..ends.len() {
+				let d = (ends[i].x - ends[j].x).powi(2) + (ends[i].y - ends[j].y).powi(2);
+				if d > distance { distance = d; pair = (ends[i], ends[j]); }
+			}
+		}
+		return (true, Segment { a: pair.0, b: pair.1 });
+	}
+	// the axioms return lines which cross the frame, keep those crossing the paper
+	pub fn keep (&self, lines: Vec<Line>) -> Vec<Line> {
+		if self.square { return lines }
+		return lines.into_iter().filter(|line| self.clip(*line).0).collect();
+	}
+	// axioms 5, 6 and 7 fold points onto lines. the axioms only check the
+	// points land inside the frame, keep the folds which cross the paper and
+	// bring every point to somewhere on it
+	pub fn keep_folds (&self, lines: Vec<Line>, points: &[Vector]) -> Vec<Line> {
+		if self.square { return lines }
+		return self.keep(lines).into_iter()
+			.filter(|line| points.iter().all(|point| self.contains(reflect(line, *point))))
+			.collect();
+	}
+	// from the unit square to the paper's units, and back
+	pub fn to_paper (&self, point: Vector) -> Vector {
+		Vector { x: point.x * self.scale + self.offset.x, y: point.y * self.scale + self.offset.y }
+	}
+	pub fn from_paper (&self, point: Vector) -> Vector {
+		Vector { x: (point.x - self.offset.x) / self.scale, y: (point.y - self.offset.y) / self.scale }
+	}
+	// u . p = d in the unit square is u . p = d * scale + u . offset in the paper's units
+	pub fn line_to_paper (&self, line: Line) -> Line {
+		Line { u: line.u, d: line.d * self.scale + line.u.x * self.offset.x + line.u.y * self.offset.y }
+	}
+}
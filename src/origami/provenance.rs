@@ -0,0 +1,74 @@
+use rabbit_ear as ear;
+use self::ear::Vector;
+use self::ear::Line;
+
+// how much history the containers keep about how each point and line was made
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Provenance {
+	None, // only counts, and the axiom of the first construction
+	First, // the parents of the first construction
+	All, // the parents of every construction
+}
+
+// one construction of a point or line. the axiom is 1-7 for lines, and 0 for
+// points, which are the intersection of two lines. points and lines are the
+// parents the axiom was given, in the order the axiom takes them.
+// an origin without any parents marks the initial geometry
+#[derive(Clone)]
+pub struct Origin {
+	pub axiom: u8,
+	pub points: Vec<Vector>,
+	pub lines: Vec<Line>,
+}
+
+pub fn parse_provenance (string: &str) -> Option<Provenance> {
+	match string {
+		"none" => Some(Provenance::None),
+		"first" => Some(Provenance::First),
+		"all" => Some(Provenance::All),
+		_ => None,
+	}
+}
+
+impl Origin {
+	pub fn initial (&self) -> bool { self.points.len() == 0 && self.lines.len() == 0 }
+}
+
+impl Provenance {
+	pub fn tracking (&self) -> bool { *self != Provenance::None }
+	// the origins an entry pushed without any starts with, it is part of
+	// the initial geometry
+	pub fn start (&self) -> Vec<Origin> {
+		if !self.tracking() { return vec![] }
+		return vec![Origin { axiom: 0, points: vec![], lines: vec![] }];
+	}
+	// the origins kept of the ones an entry is pushed with
+	pub fn keep (&self, origins: &Vec<Origin>) -> Vec<Origin> {
+		match *self {
+			Provenance::None => vec![],
+			Provenance::First => origins.iter().take(1).cloned().collect(),
+			Provenance::All => origins.clone(),
+		}
+	}
+	// a repeated construction was found, keep its origins if needed
+	pub fn repeat (&self, existing: &mut Vec<Origin>, origins: &Vec<Origin>) {
+		match *self {
+			Provenance::None => {},
+			Provenance::First => if existing.is_empty() {
+				existing.extend(origins.iter().take(1).cloned());
+			},
+			Provenance::All => existing.extend(origins.iter().cloned()),
+		}
+	}
+}
+
+// a single origin, only built when the container is tracking
+pub fn make_origin (
+	provenance: Provenance,
+	axiom: u8,
+	points: &[Vector],
+	lines: &[Line]
+) -> Vec<Origin> {
+	if !provenance.tracking() { return vec![] }
+	return vec![Origin { axiom, points: points.to_vec(), lines: lines.to_vec() }];
+}
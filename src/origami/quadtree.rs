@@ -2,6 +2,13 @@ use std::fmt;
 use rabbit_ear as ear;
 use self::ear::Vector;
 use origami::CountPoint;
+use origami::Origin;
+use origami::Provenance;
+use origami::PointIndex;
+use origami::Tolerance;
+use origami::EdgeCount;
+use origami::tolerance::default_tolerance;
+use origami::index::refile_points;
 // use math::EPSILON;
 const EPSILON: f64 = 1.0e-16;
 
@@ -26,23 +33,44 @@ const BUCKET_SIZE: usize = 1000;
 // 1: 0.5
 // 2: 0.25
 // 3: 0.125
+// "top" is the smaller y, it's the top when +Y is down.
+// when provenance is tracked, "origins" mirrors "points": origins[k] are
+// the constructions of the point points[k]. otherwise it's empty.
+// "round" is the round given to points made with push. every node has the
+// root's tolerance, and the root tallies the edges
 pub struct QuadTree {
 	pub quadrants: Vec<QuadTree>,
 	pub points: Vec<CountPoint>,
+	pub origins: Vec<Vec<Origin>>,
+	pub provenance: Provenance,
+	pub round: u8,
+	pub tolerance: Tolerance,
+	pub edges: EdgeCount,
 	pub level: u16,
 	pub top: f64,
 	pub left: f64
 }
 
 // this is hard-coded to a unit square with left corner at (0, 0)
-pub fn make_tree () -> QuadTree { QuadTree {
-	quadrants: vec![],
-	points: vec![],
-	level: 0, top: 0.0, left: 0.0
-}}
+pub fn make_tree () -> QuadTree {
+	return make_tracked_tree(Provenance::None);
+}
+
+pub fn make_tracked_tree (provenance: Provenance) -> QuadTree {
+	return make_node(provenance, default_tolerance(), 0, 0.0, 0.0);
+}
 
-fn make_node (level: u16, top: f64, left: f64) -> QuadTree {
-	return QuadTree { quadrants: vec![], points: vec![], level, top, left };
+fn make_node (provenance: Provenance, tolerance: Tolerance, level: u16, top: f64, left: f64) -> QuadTree {
+	return QuadTree {
+		quadrants: vec![],
+		points: vec![],
+		origins: vec![],
+		provenance,
+		round: 0,
+		tolerance,
+		edges: EdgeCount::default(),
+		level, top, left
+	};
 }
 
 fn dimensions (level: u16) -> (f64, f64) {
@@ -50,6 +78,14 @@ fn dimensions (level: u16) -> (f64, f64) {
 	return (QUAD_WIDTH / denom, QUAD_HEIGHT / denom);
 }
 
+// where a node's children meet, x_axis splits left from right, and
+// y_axis splits the lower (0, 1) from the upper (2, 3) quadrants
+fn axes (node: &QuadTree) -> (f64, f64) {
+	// this is the next level's w and h
+	let (w, h) = dimensions(node.level + 1);
+	return (node.left + w, node.top + h);
+}
+
 // this makes the equal to the axis line on the + side
 fn which_quad (point: &Vector, x_axis: f64, y_axis: f64) -> usize {
 	let x_side = if point.x < x_axis as f64 {0} else {1};
@@ -57,93 +93,185 @@ fn which_quad (point: &Vector, x_axis: f64, y_axis: f64) -> usize {
 	return x_side + y_side;
 }
 
+// the quadrant of a point first, then the quadrants across any axis the
+// point is near. near is half of one of GridVec's cells, the same distance
+// its neighboring cells cover
+fn nearby_quads (point: &Vector, x_axis: f64, y_axis: f64, tolerance: &Tolerance) -> Vec<usize> {
+	let near = 0.5 / tolerance.point_cells();
+	let index = which_quad(point, x_axis, y_axis);
+	let mut quads = vec![index];
+	let near_x = (point.x - x_axis).abs() < near;
+	let near_y = (point.y - y_axis).abs() < near;
+	if near_x { quads.push(index ^ 1); }
+	if near_y { quads.push(index ^ 2); }
+	if near_x && near_y { quads.push(index ^ 3); }
+	return quads;
+}
+
 fn split (node: &mut QuadTree) {
 	// this is the next level's w and h
 	let (w, h) = dimensions(node.level + 1);
-	if w < EPSILON || h < EPSILON {
-		println!("bucket dimensions below epsilon at level {}, suggest increase bucket size", node.level);
-		return;
-	}
-	let y_axis = node.top + h;
-	let x_axis = node.left + w;
+	// too small to split, the leaf keeps every point
+	if w < EPSILON || h < EPSILON { return }
+	let (x_axis, y_axis) = axes(node);
 	let tops = [node.top, node.top, y_axis, y_axis];
 	let lefts = [node.left, x_axis, node.left, x_axis];
 	for i in 0..4 {
-		node.quadrants.push(make_node(node.level + 1, tops[i], lefts[i]));
+		node.quadrants.push(make_node(node.provenance, node.tolerance, node.level + 1, tops[i], lefts[i]));
 	}
-	// println!("pushing couted point...");
-	for i in 0..node.points.len() {
-		let index = which_quad(&node.points[i].0, x_axis, y_axis);
-		node.quadrants[index].push_counted_point(node.points[i]);
+	let points = std::mem::replace(&mut node.points, Vec::new());
+	let mut origins = std::mem::replace(&mut node.origins, Vec::new()).into_iter();
+	for i in 0..points.len() {
+		let index = which_quad(&points[i].0, x_axis, y_axis);
+		insert(&mut node.quadrants[index], points[i], origins.next());
 	}
-	// println!("  done pushing couted point");
-	node.points.clear();
 }
 
 fn find_leaf<'a> (node: &'a mut QuadTree, point: &Vector) -> &'a mut QuadTree {
 	if node.quadrants.len() == 0 { return node }
-	// this is the next level's w and h
-	let (w, h) = dimensions(node.level + 1);
-	let x_axis = node.top + h;
-	let y_axis = node.left + w;
+	let (x_axis, y_axis) = axes(node);
 	let index = which_quad(point, x_axis, y_axis);
 	return find_leaf(&mut node.quadrants[index], point);
 }
 
-// if match found, returns true AND increments matched point counter
-fn find_point_match (points: &mut Vec<CountPoint>, point: &Vector) -> bool {
-	for i in 0..points.len() {
-		if point.equivalent(points[i].0) {
-			points[i].1 += 1;
-			return true;
+// add a point to its leaf without checking for a match. "origins" are
+// already the point's starting origins, None if untracked
+fn insert (node: &mut QuadTree, point: CountPoint, origins: Option<Vec<Origin>>) {
+	let leaf = find_leaf(node, &point.0);
+	leaf.points.push(point);
+	if let Some(origins) = origins { leaf.origins.push(origins); }
+	if leaf.points.len() >= BUCKET_SIZE { split(leaf) }
+}
+
+// the quadrant indices down to the leaf holding a point equivalent to
+// this one, and its index in that leaf. "nearest" becomes how far the match
+// is, or without one, how far the nearest point searched is
+fn find (node: &QuadTree, point: &Vector, path: &mut Vec<usize>, nearest: &mut f64) -> Option<usize> {
+	if node.quadrants.len() == 0 {
+		for i in 0..node.points.len() {
+			let distance = node.tolerance.point_distance(point, &node.points[i].0);
+			if node.tolerance.within(distance) {
+				*nearest = distance;
+				return Some(i);
+			}
+			if distance < *nearest { *nearest = distance; }
 		}
+		return None;
+	}
+	let (x_axis, y_axis) = axes(node);
+	for index in nearby_quads(point, x_axis, y_axis, &node.tolerance) {
+		path.push(index);
+		if let Some(i) = find(&node.quadrants[index], point, path, nearest) { return Some(i) }
+		path.pop();
+	}
+	return None;
+}
+
+fn node_at<'a> (node: &'a QuadTree, path: &[usize]) -> &'a QuadTree {
+	if path.len() == 0 { return node }
+	return node_at(&node.quadrants[path[0]], &path[1..]);
+}
+
+fn node_at_mut<'a> (node: &'a mut QuadTree, path: &[usize]) -> &'a mut QuadTree {
+	if path.len() == 0 { return node }
+	return node_at_mut(&mut node.quadrants[path[0]], &path[1..]);
+}
+
+fn flatten_recurse (node: &QuadTree, count: u64, list: &mut Vec<CountPoint>) {
+	for i in 0..node.points.len() {
+		if node.points[i].1 >= count { list.push(node.points[i]) }
+	}
+	for i in 0..node.quadrants.len() {
+		flatten_recurse(&node.quadrants[i], count, list);
 	}
-	return false;
 }
 
-fn flatten_recurse (node: &QuadTree, list: &mut Vec<CountPoint>) {
-	for i in 0..node.points.len() { list.push(node.points[i]) }
+// every point with its origins, emptying the tree
+fn drain_recurse (node: &mut QuadTree, list: &mut Vec<(CountPoint, Vec<Origin>)>) {
+	let points = std::mem::replace(&mut node.points, Vec::new());
+	let mut origins = std::mem::replace(&mut node.origins, Vec::new()).into_iter();
+	for i in 0..points.len() {
+		list.push((points[i], origins.next().unwrap_or(Vec::new())));
+	}
 	for i in 0..node.quadrants.len() {
-		flatten_recurse(&node.quadrants[i], list);
+		drain_recurse(&mut node.quadrants[i], list);
 	}
+	node.quadrants.clear();
 }
 
-impl QuadTree {
-	pub fn push_counted_point (&mut self, point: CountPoint) {
-		let leaf = find_leaf(self, &point.0);
-		if !find_point_match(&mut leaf.points, &point.0) {
-			leaf.points.push(point);
-		}
-		if leaf.points.len() >= BUCKET_SIZE { split(leaf) }
+impl PointIndex for QuadTree {
+	fn make_empty (&self) -> QuadTree {
+		let mut tree = make_tracked_tree(self.provenance);
+		tree.round = self.round;
+		tree.tolerance = self.tolerance;
+		return tree;
+	}
+	fn provenance (&self) -> Provenance { self.provenance }
+	fn round (&self) -> u8 { self.round }
+	fn set_round (&mut self, round: u8) { self.round = round; }
+	fn tolerance (&self) -> Tolerance { self.tolerance }
+	fn set_tolerance (&mut self, tolerance: Tolerance) {
+		let points = self.drain();
+		self.tolerance = tolerance;
+		refile_points(self, points);
 	}
-	// pub fn push (&mut self, point: &Vector) {
-	pub fn push (&mut self, point: Vector) {
-		let leaf = find_leaf(self, &point);
-		if !find_point_match(&mut leaf.points, &point) {
-			leaf.points.push((point, 1));
+	fn edges (&self) -> EdgeCount { self.edges }
+	fn add_edges (&mut self, edges: &EdgeCount) { self.edges.add(edges); }
+	fn push_counted (&mut self, point: CountPoint, origins: &Vec<Origin>) {
+		let kept = if self.provenance.tracking() { Some(self.provenance.keep(origins)) }
+			else { None };
+		insert(self, point, kept);
+	}
+	fn increment_nearest_by (&mut self, point: &Vector, count: u64, origins: &Vec<Origin>) -> (bool, f64) {
+		let mut path: Vec<usize> = Vec::new();
+		let mut nearest = std::f64::INFINITY;
+		let i = match find(self, point, &mut path, &mut nearest) {
+			Some(i) => i,
+			None => return (false, nearest),
+		};
+		let provenance = self.provenance;
+		let leaf = node_at_mut(self, &path);
+		leaf.points[i].1 += count;
+		if provenance.tracking() {
+			provenance.repeat(&mut leaf.origins[i], origins);
 		}
-		if leaf.points.len() >= BUCKET_SIZE { split(leaf) }
-	}
-	// if match found, returns true AND increment the matched point counter
-	pub fn increment_match (&mut self, point: &Vector) -> bool {
-		let leaf = find_leaf(self, &point);
-		return find_point_match(&mut leaf.points, &point);
-	}
-	pub fn merge (&mut self, other: &QuadTree) {
-		// println!("merging...");
-		let list = other.flatten();
-		for i in 0..list.len() {
-			self.push_counted_point(list[i]);
+		return (true, nearest);
+	}
+	fn find_origins (&self, point: &Vector) -> Option<&Vec<Origin>> {
+		let mut path: Vec<usize> = Vec::new();
+		let mut nearest = std::f64::INFINITY;
+		let i = find(self, point, &mut path, &mut nearest)?;
+		return if self.provenance.tracking() { Some(&node_at(self, &path).origins[i]) }
+			else { Some(&EMPTY_ORIGINS) };
+	}
+	fn drain (&mut self) -> Vec<(CountPoint, Vec<Origin>)> {
+		let mut list: Vec<(CountPoint, Vec<Origin>)> = Vec::new();
+		drain_recurse(self, &mut list);
+		return list;
+	}
+	fn merge (&mut self, other: &mut QuadTree) {
+		self.edges.add(&other.edges);
+		other.edges = EdgeCount::default();
+		for (point, origins) in other.drain() {
+			// an untracked tree's points join with no origins
+			let origins = if self.provenance.tracking() { Some(origins) } else { None };
+			insert(self, point, origins);
 		}
-		// println!("done merging");
 	}
-	pub fn flatten (&self) -> Vec<CountPoint> {
+	fn flatten_filter (&self, count: u64) -> Vec<CountPoint> {
 		let mut list: Vec<CountPoint> = Vec::new();
-		flatten_recurse(self, &mut list);
+		flatten_recurse(self, count, &mut list);
 		return list;
 	}
+	fn len (&self) -> usize {
+		let mut count = self.points.len();
+		for i in 0..self.quadrants.len() { count += self.quadrants[i].len(); }
+		return count;
+	}
 }
 
+static EMPTY_ORIGINS: Vec<Origin> = Vec::new();
+
 impl fmt::Debug for QuadTree {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		f.debug_struct("QuadTree")
@@ -0,0 +1,345 @@
+use origami::CountSegment;
+use origami::Polygon;
+use origami::style::Style;
+use origami::style::Theme;
+
+// a density image of the lines. every segment adds its count along its
+// length into a buffer of floats, spread between the 4 nearest pixels, so
+// lines are anti-aliased and overlapping lines add up. the buffer is tone
+// mapped with log(1 + v) / log(1 + max) into an 8 bit grayscale png.
+
+const MARGIN: f64 = 0.01;
+// distance between samples along a segment, in pixels
+const STEP: f64 = 0.5;
+// the largest side, the buffer is up to this squared floats (2 GiB)
+pub const MAX_PNG_SIZE: usize = 16384;
+
+pub struct Raster {
+	pub width: usize,
+	pub height: usize,
+	pub buffer: Vec<f64>,
+}
+
+// "size" is the larger side in pixels. the image covers the same area as the svg
+pub fn make_raster (segments: &Vec<CountSegment>, paper: &Polygon, size: usize) -> Raster {
+	let (min, max) = paper.bounds();
+	let (w, h) = (max.x - min.x + 2.0 * MARGIN, max.y - min.y + 2.0 * MARGIN);
+	let scale = size as f64 / w.max(h);
+	let width = std::cmp::max(1, (w * scale).round() as usize);
+	let height = std::cmp::max(1, (h * scale).round() as usize);
+	let mut raster = Raster { width, height, buffer: vec![0.0; width * height] };
+	for i in 0..segments.len() {
+		let (a, b) = (segments[i].0.a, segments[i].0.b);
+		let (ax, ay) = ((a.x - min.x + MARGIN) * scale, (a.y - min.y + MARGIN) * scale);
+		let (bx, by) = ((b.x - min.x + MARGIN) * scale, (b.y - min.y + MARGIN) * scale);
+		let length = ((bx - ax).powi(2) + (by - ay).powi(2)).sqrt();
+		let samples = std::cmp::max(1, (length / STEP).ceil() as usize);
+		let weight = segments[i].1 as f64 * length / samples as f64;
+		for k in 0..samples {
+			let t = (k as f64 + 0.5) / samples as f64;
+			raster.splat(ax + (bx - ax) * t, ay + (by - ay) * t, weight);
+		}
+	}
+	return raster;
+}
+
+impl Raster {
+	// add weight at (x, y) in pixels, pixel centers are at +0.5
+	fn splat (&mut self, x: f64, y: f64, weight: f64) {
+		let (fx, fy) = (x - 0.5, y - 0.5);
+		let (x0, y0) = (fx.floor(), fy.floor());
+		let (dx, dy) = (fx - x0, fy - y0);
+		let corners = [
+			(x0, y0, (1.0 - dx) * (1.0 - dy)),
+			(x0 + 1.0, y0, dx * (1.0 - dy)),
+			(x0, y0 + 1.0, (1.0 - dx) * dy),
+			(x0 + 1.0, y0 + 1.0, dx * dy),
+		];
+		for &(px, py, share) in corners.iter() {
+			if px < 0.0 || py < 0.0 { continue }
+			let (px, py) = (px as usize, py as usize);
+			if px >= self.width || py >= self.height { continue }
+			self.buffer[py * self.width + px] += weight * share;
+		}
+	}
+	// one byte per pixel, the theme's foreground where the lines are densest
+	pub fn tone_map (&self, style: &Style) -> Vec<u8> {
+		let max = self.buffer.iter().fold(0.0, |m: f64, v| m.max(*v));
+		let denominator = (1.0 + max).ln();
+		return self.buffer.iter().map(|v| {
+			let t = if denominator > 0.0 { (1.0 + v).ln() / denominator } else { 0.0 };
+			let gray = (t * 255.0).round() as u8;
+			match style.theme { Theme::Dark => gray, Theme::Light => 255 - gray }
+		}).collect();
+	}
+	pub fn png (&self, style: &Style) -> Vec<u8> {
+		return encode_png(&self.tone_map(style), self.width, self.height);
+	}
+}
+
+fn crc32 (bytes: &[u8]) -> u32 {
+	let mut crc: u32 = 0xffffffff;
+	for byte in bytes.iter() {
+		crc ^= *byte as u32;
+		for _k in 0..8 {
+			crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 };
+		}
+	}
+	return !crc;
+}
+
+fn adler32 (bytes: &[u8]) -> u32 {
+	let (mut a, mut b): (u32, u32) = (1, 0);
+	for byte in bytes.iter() {
+		a = (a + *byte as u32) % 65521;
+		b = (b + a) % 65521;
+	}
+	return (b << 16) | a;
+}
+
+// deflate writes bits starting from the lowest bit of each byte
+struct Bits {
+	bytes: Vec<u8>,
+	bit: u32,
+}
+
+impl Bits {
+	fn push (&mut self, value: u32, count: u32) {
+		for i in 0..count {
+			if self.bit == 0 { self.bytes.push(0); }
+			let last = self.bytes.len() - 1;
+			self.bytes[last] |= (((value >> i) & 1) as u8) << self.bit;
+			self.bit = (self.bit + 1) % 8;
+		}
+	}
+	// huffman codes are written from their highest bit
+	fn push_code (&mut self, code: u32, count: u32) {
+		for i in (0..count).rev() { self.push((code >> i) & 1, 1); }
+	}
+	// a literal byte, or 256 to 287, with the fixed huffman codes
+	fn push_symbol (&mut self, symbol: u32) {
+		match symbol {
+			0..=143 => self.push_code(0x30 + symbol, 8),
+			144..=255 => self.push_code(0x190 + symbol - 144, 9),
+			256..=279 => self.push_code(symbol - 256, 7),
+			_ => self.push_code(0xc0 + symbol - 280, 8),
+		}
+	}
+}
+
+const LENGTH_BASE: [u32; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31,
+	35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u32; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2,
+	3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+
+// one fixed huffman block. the only matches are runs of the previous byte
+// (distance 1), which is most of a density image's background
+fn deflate (data: &[u8]) -> Vec<u8> {
+	let mut bits = Bits { bytes: Vec::new(), bit: 0 };
+	bits.push(1, 1); // the final block
+	bits.push(1, 2); // fixed huffman codes
+	let mut i: usize = 0;
+	while i < data.len() {
+		let mut run: usize = 0;
+		if i > 0 {
+			while run < 258 && i + run < data.len() && data[i + run] == data[i - 1] { run += 1; }
+		}
+		if run < 3 {
+			bits.push_symbol(data[i] as u32);
+			i += 1;
+			continue;
+		}
+		let code = LENGTH_BASE.iter().rposition(|base| *base as usize <= run).unwrap();
+		bits.push_symbol(257 + code as u32);
+		bits.push(run as u32 - LENGTH_BASE[code], LENGTH_EXTRA[code]);
+		bits.push_code(0, 5); // distance 1
+		i += run;
+	}
+	bits.push_symbol(256); // end of block
+	return bits.bytes;
+}
+
+fn chunk (png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+	png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+	let start = png.len();
+	png.extend_from_slice(kind);
+	png.extend_from_slice(data);
+	let crc = crc32(&png[start..]);
+	png.extend_from_slice(&crc.to_be_bytes());
+}
+
+// an 8 bit grayscale png, one byte per pixel, row by row
+pub fn encode_png (pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+	let mut png: Vec<u8> = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+	let mut header: Vec<u8> = Vec::new();
+	header.extend_from_slice(&(width as u32).to_be_bytes());
+	header.extend_from_slice(&(height as u32).to_be_bytes());
+	header.extend_from_slice(&[8, 0, 0, 0, 0]); // 8 bits, grayscale, no interlace
+	chunk(&mut png, b"IHDR", &header);
+	// every row starts with its filter, 0 is none
+	let mut rows: Vec<u8> = Vec::with_capacity((width + 1) * height);
+	for y in 0..height {
+		rows.push(0);
+		rows.extend_from_slice(&pixels[y * width..(y + 1) * width]);
+	}
+	let mut zlib: Vec<u8> = vec![0x78, 0x01];
+	zlib.extend(deflate(&rows));
+	zlib.extend_from_slice(&adler32(&rows).to_be_bytes());
+	chunk(&mut png, b"IDAT", &zlib);
+	chunk(&mut png, b"IEND", &[]);
+	return png;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// reads bits from the lowest bit of each byte, like deflate writes them
+	struct Reader<'a> {
+		bytes: &'a [u8],
+		bit: usize,
+	}
+
+	impl<'a> Reader<'a> {
+		fn read (&mut self, count: u32) -> u32 {
+			let mut value = 0;
+			for i in 0..count {
+				let byte = self.bytes[self.bit / 8];
+				value |= (((byte >> (self.bit % 8)) & 1) as u32) << i;
+				self.bit += 1;
+			}
+			return value;
+		}
+		fn read_code (&mut self, count: u32) -> u32 {
+			let mut code = 0;
+			for _i in 0..count { code = (code << 1) | self.read(1); }
+			return code;
+		}
+		// the fixed huffman codes, shortest first
+		fn read_symbol (&mut self) -> u32 {
+			let code = self.read_code(7);
+			if code <= 0x17 { return code + 256 }
+			let code = (code << 1) | self.read(1);
+			if code >= 0x30 && code <= 0xbf { return code - 0x30 }
+			if code >= 0xc0 && code <= 0xc7 { return code - 0xc0 + 280 }
+			let code = (code << 1) | self.read(1);
+			assert!(code >= 0x190 && code <= 0x1ff);
+			return code - 0x190 + 144;
+		}
+	}
+
+	const DISTANCE_BASE: [usize; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193,
+		257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+
+	// a decoder for fixed huffman blocks, which is all deflate writes
+	fn inflate (bytes: &[u8]) -> Vec<u8> {
+		let mut reader = Reader { bytes, bit: 0 };
+		let mut data: Vec<u8> = Vec::new();
+		loop {
+			let last = reader.read(1);
+			assert_eq!(reader.read(2), 1, "not a fixed huffman block");
+			loop {
+				let symbol = reader.read_symbol();
+				if symbol < 256 { data.push(symbol as u8); continue }
+				if symbol == 256 { break }
+				let code = (symbol - 257) as usize;
+				let length = (LENGTH_BASE[code] + reader.read(LENGTH_EXTRA[code])) as usize;
+				let distance_code = reader.read_code(5) as usize;
+				let extra = if distance_code < 4 { 0 } else { (distance_code as u32 - 2) / 2 };
+				let distance = DISTANCE_BASE[distance_code] + reader.read(extra) as usize;
+				for _k in 0..length {
+					let byte = data[data.len() - distance];
+					data.push(byte);
+				}
+			}
+			if last == 1 { break }
+		}
+		assert_eq!(reader.bit / 8, bytes.len() - 1, "bytes after the last block");
+		return data;
+	}
+
+	fn be32 (bytes: &[u8]) -> u32 {
+		return u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+	}
+
+	// checks the signature, every chunk's length and crc, and the chunk order.
+	// returns the header fields and the decoded rows
+	fn decode (png: &[u8]) -> ((u32, u32, [u8; 5]), Vec<u8>) {
+		assert_eq!(&png[..8], &[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]);
+		let mut chunks: Vec<([u8; 4], &[u8])> = Vec::new();
+		let mut i = 8;
+		while i < png.len() {
+			let length = be32(&png[i..]) as usize;
+			let kind = [png[i + 4], png[i + 5], png[i + 6], png[i + 7]];
+			let data = &png[i + 8..i + 8 + length];
+			assert_eq!(crc32(&png[i + 4..i + 8 + length]), be32(&png[i + 8 + length..]),
+				"crc of {}", String::from_utf8_lossy(&kind));
+			chunks.push((kind, data));
+			i += 12 + length;
+		}
+		assert_eq!(i, png.len());
+		let kinds: Vec<&[u8; 4]> = chunks.iter().map(|el| &el.0).collect();
+		assert_eq!(kinds, vec![b"IHDR", b"IDAT", b"IEND"]);
+		let header = chunks[0].1;
+		assert_eq!(header.len(), 13);
+		let fields = [header[8], header[9], header[10], header[11], header[12]];
+		let zlib = chunks[1].1;
+		assert_eq!((zlib[0] as u32 * 256 + zlib[1] as u32) % 31, 0);
+		assert_eq!(zlib[0] & 0x0f, 8);
+		let rows = inflate(&zlib[2..zlib.len() - 4]);
+		assert_eq!(adler32(&rows), be32(&zlib[zlib.len() - 4..]));
+		assert_eq!(chunks[2].1.len(), 0);
+		return ((be32(header), be32(&header[4..]), fields), rows);
+	}
+
+	fn rows (pixels: &[u8], width: usize) -> Vec<u8> {
+		return pixels.chunks(width).flat_map(|row| {
+			let mut row = row.to_vec();
+			row.insert(0, 0);
+			row
+		}).collect();
+	}
+
+	#[test]
+	fn checksums () {
+		assert_eq!(crc32(b"123456789"), 0xcbf43926);
+		assert_eq!(crc32(b"IEND"), 0xae426082);
+		assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+		assert_eq!(adler32(&[]), 1);
+	}
+
+	#[test]
+	fn noise () {
+		let (width, height) = (37, 11);
+		let mut seed: u32 = 12345;
+		let pixels: Vec<u8> = (0..width * height).map(|_| {
+			seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+			(seed >> 24) as u8
+		}).collect();
+		let (header, decoded) = decode(&encode_png(&pixels, width, height));
+		assert_eq!(header, (37, 11, [8, 0, 0, 0, 0]));
+		assert_eq!(decoded, rows(&pixels, width));
+	}
+
+	// every run length deflate can write, and runs longer than one match
+	#[test]
+	fn runs () {
+		let mut pixels: Vec<u8> = Vec::new();
+		for length in 1..600 {
+			pixels.extend(std::iter::repeat((length % 251) as u8).take(length));
+		}
+		let width = 1000;
+		pixels.resize(width * (pixels.len() / width + 1), 7);
+		let height = pixels.len() / width;
+		let (header, decoded) = decode(&encode_png(&pixels, width, height));
+		assert_eq!(header, (width as u32, height as u32, [8, 0, 0, 0, 0]));
+		assert_eq!(decoded, rows(&pixels, width));
+	}
+
+	#[test]
+	fn one_pixel () {
+		let (header, decoded) = decode(&encode_png(&[200], 1, 1));
+		assert_eq!(header, (1, 1, [8, 0, 0, 0, 0]));
+		assert_eq!(decoded, vec![0, 200]);
+	}
+}
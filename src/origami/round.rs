@@ -0,0 +1,90 @@
+use origami;
+use origami::PointIndex;
+use origami::LineIndex;
+use origami::RoundPlan;
+use origami::CountPoint;
+use origami::CountLine;
+use origami::Polygon;
+
+// points and lines keep the round they were made in as a u8, round 0 is
+// the initial geometry
+pub const MAX_ROUNDS: usize = 255;
+
+// one round: every axiom in the plan is built from the points and lines of
+// the previous rounds, then the new lines are crossed with each other and the
+// old lines to make new points. everything new is merged into the containers.
+// round is zero-indexed, what it makes is marked round + 1, so it has to be
+// less than MAX_ROUNDS
+pub fn make_round<P: PointIndex, L: LineIndex + Send + Sync> (
+	round: usize,
+	plan: &RoundPlan,
+	point_quadtree: &mut P,
+	line_container: &mut L,
+	boundary: &Polygon
+) {
+	// all axioms will be built from function arguments points and lines
+	// from the previous round (make points into Vector from the quadtree)
+	println!("round start {} points, {} lines", point_quadtree.len(), line_container.len());
+
+	// new lines is all the lines made in THIS round
+	// let mut new_lines: Vec<(Line, u64)> = Vec::new();
+	let mut new_line_container: L = line_container.make_empty();
+	// lines made in the first round (0) are marked round 1, after the initial geometry
+	assert!(round < MAX_ROUNDS, "round {} can't be marked, there are at most {} rounds", round, MAX_ROUNDS);
+	new_line_container.set_round((round + 1) as u8);
+	// 1. compute all axioms for this round. each axiom only builds from the
+	// points and lines repeated often enough to pass its filter. axioms which
+	// share a filter share the flattened list
+	let mut filtered_points: Vec<(u64, Vec<CountPoint>)> = Vec::new();
+	let mut filtered_lines: Vec<(u64, Vec<CountLine>)> = Vec::new();
+	for axiom in plan.axioms.iter() {
+		let min_points = plan.min_points(*axiom);
+		let min_lines = plan.min_lines(*axiom);
+		if !filtered_points.iter().any(|el| el.0 == min_points) {
+			filtered_points.push((min_points, point_quadtree.flatten_filter(min_points)));
+		}
+		if !filtered_lines.iter().any(|el| el.0 == min_lines) {
+			filtered_lines.push((min_lines, line_container.flatten_filter(min_lines)));
+		}
+		let points = &filtered_points.iter().find(|el| el.0 == min_points).unwrap().1;
+		let lines = &filtered_lines.iter().find(|el| el.0 == min_lines).unwrap().1;
+		if min_points > 1 || min_lines > 1 {
+			println!("axiom {} filter: {} points (count >= {}), {} lines (count >= {})",
+				axiom, points.len(), min_points, lines.len(), min_lines);
+		}
+		match axiom {
+			1 => origami::make_axiom1(points, line_container, &mut new_line_container, boundary, plan.threads),
+			2 => origami::make_axiom2(points, line_container, &mut new_line_container, boundary, plan.threads),
+			3 => origami::make_axiom3(points, lines, line_container, &mut new_line_container, boundary, plan.threads),
+			4 => origami::make_axiom4(points, lines, line_container, &mut new_line_container, boundary, plan.threads),
+			5 => origami::make_axiom5(points, lines, line_container, &mut new_line_container, boundary, plan.threads),
+			6 => origami::make_axiom6(points, lines, line_container, &mut new_line_container, boundary, plan.threads),
+			7 => origami::make_axiom7(points, lines, line_container, &mut new_line_container, boundary, plan.threads),
+			_ => {}
+		}
+	}
+	// origami::make_axiom5(&pts_ax5, &lns_ax5, line_container, &mut new_line_container, boundary);
+	// // origami::shortcut_axiom6(&points, &lines, line_container, &mut new_line_container, boundary);
+	// origami::make_axiom6(&pts_ax6, &lns_ax6, line_container, &mut new_line_container, boundary);
+	// origami::make_axiom7(&pts_ax7, &lns_ax7, line_container, &mut new_line_container, boundary);
+	// todo: list more axioms
+	// 2. compute new intersection points
+	// let mut new_points: Vec<(Vector, u64)> = if make_pts { origami::make_intersections(
+	// 	points, &mut new_lines) } else { Vec::new() };
+	// let mut new_points: Vec<(Vector, u64)> = origami::make_intersections(
+	// 	points, &mut new_lines);
+
+	let new_lines = new_line_container.flatten();
+	let old_lines = line_container.flatten();
+
+	// let mut new_points: GridVec = origami::make_intersections(
+	// 	point_quadtree, &old_lines, &new_lines, boundary);
+	let mut new_points: P = if plan.intersections {
+		origami::make_intersections(point_quadtree, &old_lines, &new_lines, boundary)
+	} else { point_quadtree.make_empty() };
+
+	// point_quadtree, lines, &mut new_lines, boundary);
+	// 3. merge points and lines from this new round
+	point_quadtree.merge(&mut new_points);
+	line_container.merge(&mut new_line_container);
+}
@@ -0,0 +1,159 @@
+use std::fs;
+use rabbit_ear as ear;
+use self::ear::Vector;
+use self::ear::Line;
+use self::ear::axioms::axiom1;
+use origami::PointIndex;
+use origami::LineIndex;
+use origami::Polygon;
+
+// extra initial geometry, read from a text file, one entry per line:
+//   point X Y             a point
+//   line X1 Y1 X2 Y2      the crease through two points
+// anything after a # is a comment. coordinates are in the paper's units (a
+// hexagon's center is at 0 0, a4 is 0.707 wide), the same as --find-point.
+// the paper's own corners and sides are always part of the initial geometry.
+pub struct Seed {
+	pub points: Vec<Vector>,
+	pub lines: Vec<Line>,
+}
+
+fn parse_coordinates (words: &[&str], count: usize, number: usize) -> Result<Vec<f64>, String> {
+	if words.len() != count {
+		return Err(format!("line {}: expected {} numbers, found {}", number, count, words.len()));
+	}
+	return words.iter()
+		.map(|word| word.parse::<f64>()
+			.map_err(|_| format!("line {}: expected a number, found \"{}\"", number, word)))
+		.collect();
+}
+
+pub fn parse_seed (text: &str, paper: &Polygon) -> Result<Seed, String> {
+	let mut seed = Seed { points: Vec::new(), lines: Vec::new() };
+	for (i, row) in text.lines().enumerate() {
+		let number = i + 1;
+		let row = match row.find('#') { Some(end) => &row[..end], None => row };
+		let words: Vec<&str> = row.split_whitespace().collect();
+		if words.len() == 0 { continue }
+		match words[0] {
+			"point" => {
+				let n = parse_coordinates(&words[1..], 2, number)?;
+				let point = paper.from_paper(Vector { x: n[0], y: n[1] });
+				if !paper.contains(point) {
+					return Err(format!("line {}: point ({}, {}) is outside the paper", number, n[0], n[1]));
+				}
+				seed.points.push(point);
+			}
+			"line" => {
+				let n = parse_coordinates(&words[1..], 4, number)?;
+				let (a, b) = (paper.from_paper(Vector { x: n[0], y: n[1] }), paper.from_paper(Vector { x: n[2], y: n[3] }));
+				if a.equivalent(b) {
+					return Err(format!("line {}: ({}, {}) and ({}, {}) are the same point, they make no crease",
+						number, n[0], n[1], n[2], n[3]));
+				}
+				let line = match paper.keep(axiom1(a, b, paper.frame)).first() {
+					Some(line) => *line,
+					None => return Err(format!("line {}: no crease through ({}, {}) ({}, {}) crosses the paper",
+						number, n[0], n[1], n[2], n[3])),
+				};
+				seed.lines.push(line);
+			}
+			_ => return Err(format!("line {}: unknown entry \"{}\", expected point or line", number, words[0])),
+		}
+	}
+	return Ok(seed);
+}
+
+pub fn read_seed (path: &str, paper: &Polygon) -> Result<Seed, String> {
+	let text = fs::read_to_string(path)
+		.map_err(|error| format!("can't read seed file {}: {}", path, error))?;
+	return parse_seed(&text, paper).map_err(|message| format!("{}: {}", path, message));
+}
+
+impl Seed {
+	// add to the initial geometry, along with every point where the seed's
+	// creases cross each other or the paper's sides. entries already there
+	// (the paper's own corners and sides, or repeats) are only added once
+	pub fn apply<P: PointIndex, L: LineIndex> (&self, paper: &Polygon, points: &mut P, lines: &mut L) {
+		for i in 0..self.lines.len() {
+			if lines.find_origins(&self.lines[i]).is_some() { continue }
+			lines.push(self.lines[i], 0);
+		}
+		let mut new_points: Vec<Vector> = self.points.clone();
+		let all_lines = lines.flatten();
+		for i in 0..self.lines.len() {
+			for j in 0..all_lines.len() {
+				let (success, point) = self.lines[i].intersect(all_lines[j].0);
+				if success && paper.contains(point) { new_points.push(point); }
+			}
+		}
+		for i in 0..new_points.len() {
+			if points.find_origins(&new_points[i]).is_some() { continue }
+			points.push(new_points[i]);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use origami::polygon::make_unit_square;
+	use origami::polygon::make_regular_polygon;
+
+	fn point (x: f64, y: f64) -> Vector { Vector { x, y } }
+
+	fn error (text: &str) -> String {
+		return parse_seed(text, &make_unit_square()).err().unwrap();
+	}
+
+	#[test]
+	fn points_and_lines () {
+		let text = "point 0.5 0.25\n\tline 0 0   1 1\npoint 1 1\n";
+		let seed = parse_seed(text, &make_unit_square()).unwrap();
+		assert_eq!(seed.points.len(), 2);
+		assert!(seed.points[0].equivalent(point(0.5, 0.25)));
+		assert!(seed.points[1].equivalent(point(1.0, 1.0)));
+		assert_eq!(seed.lines.len(), 1);
+		let diagonal = seed.lines[0];
+		assert!((diagonal.u.x * 0.3 + diagonal.u.y * 0.3 - diagonal.d).abs() < 1e-12);
+		assert!((diagonal.u.x * 0.3 + diagonal.u.y * 0.7 - diagonal.d).abs() > 0.1);
+	}
+
+	#[test]
+	fn comments_and_blank_lines () {
+		let text = "# a seed\n\n   \npoint 0.5 0.5 # the center\n#point 0.1 0.1\nline 0 0.5 1 0.5#\n";
+		let seed = parse_seed(text, &make_unit_square()).unwrap();
+		assert_eq!(seed.points.len(), 1);
+		assert_eq!(seed.lines.len(), 1);
+		assert_eq!(parse_seed("", &make_unit_square()).unwrap().points.len(), 0);
+	}
+
+	// the hexagon's coordinates are around its center
+	#[test]
+	fn paper_units () {
+		let hexagon = make_regular_polygon(6).unwrap();
+		let seed = parse_seed("point 0 0\npoint 0.9 0", &hexagon).unwrap();
+		assert!(seed.points[0].equivalent(point(0.5, 3_f64.sqrt() / 4.0)));
+		assert!(seed.points[1].equivalent(point(0.95, 3_f64.sqrt() / 4.0)));
+		let message = parse_seed("point 0.9 0.9", &hexagon).err().unwrap();
+		assert_eq!(message, "line 1: point (0.9, 0.9) is outside the paper");
+	}
+
+	#[test]
+	fn malformed_numbers () {
+		assert_eq!(error("point 0.5 half"), "line 1: expected a number, found \"half\"");
+		assert_eq!(error("\npoint 0.5"), "line 2: expected 2 numbers, found 1");
+		assert_eq!(error("point 0.5 0.5 0.5"), "line 1: expected 2 numbers, found 3");
+		assert_eq!(error("line 0 0 1,1"), "line 1: expected 4 numbers, found 3");
+		assert_eq!(error("line 0 0 1 1e"), "line 1: expected a number, found \"1e\"");
+		assert_eq!(error("point 0.5 0.5\ncircle 1 1 1"), "line 2: unknown entry \"circle\", expected point or line");
+		assert_eq!(error("point 2 0.5"), "line 1: point (2, 0.5) is outside the paper");
+	}
+
+	#[test]
+	fn degenerate_lines () {
+		assert_eq!(error("line 0.5 0.5 0.5 0.5"),
+			"line 1: (0.5, 0.5) and (0.5, 0.5) are the same point, they make no crease");
+		assert_eq!(error("line 2 0 2 1"), "line 1: no crease through (2, 0) (2, 1) crosses the paper");
+	}
+}
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use rabbit_ear as ear;
+use self::ear::Vector;
+use self::ear::Line;
+use origami::PointIndex;
+use origami::LineIndex;
+use origami::CountPoint;
+use origami::CountLine;
+use origami::Origin;
+use origami::Tolerance;
+
+// one step in a folding sequence. axiom 0 marks the point where two lines
+// cross, otherwise a fold made with an axiom from the points and lines listed.
+// depth is the round (1 for the first round) the result could first be made,
+// the initial geometry is depth 0.
+pub struct Step {
+	pub axiom: u8,
+	pub points: Vec<Vector>,
+	pub lines: Vec<Line>,
+	pub point: Option<Vector>,
+	pub line: Option<Line>,
+	pub depth: usize,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum Key {
+	Point(i64, i64),
+	Line(i64, i64, i64),
+}
+
+// coordinates are rounded to the tolerance when used as a lookup key, so the
+// parents listed in origins find the point or line the index matched them to
+fn key_scale (tolerance: &Tolerance) -> f64 { 1.0 / tolerance.epsilon }
+
+fn point_key (point: &Vector, scale: f64) -> Key {
+	Key::Point((point.x * scale).round() as i64, (point.y * scale).round() as i64)
+}
+
+// (u, d) and (-u, -d) are the same line, this is the one with positive d,
+// or if d is zero, the one with a positive normal
+pub fn canonical_line (line: &Line) -> Line {
+	let flip = line.d < 0.0 || (line.d == 0.0 && (line.u.x < 0.0 || (line.u.x == 0.0 && line.u.y < 0.0)));
+	if !flip { return *line }
+	return Line { u: Vector { x: -line.u.x, y: -line.u.y }, d: -line.d };
+}
+
+fn line_key (line: &Line, scale: f64) -> Key {
+	let line = canonical_line(line);
+	Key::Line((line.u.x * scale).round() as i64,
+		(line.u.y * scale).round() as i64,
+		(line.d * scale).round() as i64)
+}
+
+// the straight line distance, unlike the tolerance's, which is the largest
+// difference of the coordinates
+pub fn point_distance (a: &Vector, b: &Vector) -> f64 {
+	((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
+
+// the target doesn't need to be near anything, so every point is measured
+pub fn nearest_point<P: PointIndex> (points: &P, target: &Vector) -> Option<CountPoint> {
+	let mut nearest: Option<CountPoint> = None;
+	let mut distance = std::f64::INFINITY;
+	for point in points.flatten() {
+		let d = point_distance(&point.0, target);
+		if d < distance { distance = d; nearest = Some(point); }
+	}
+	return nearest;
+}
+
+// lines are measured the way the index compares them, Tolerance::line_distance
+pub fn nearest_line<L: LineIndex> (lines: &L, target: &Line) -> Option<CountLine> {
+	let tolerance = lines.tolerance();
+	let mut nearest: Option<CountLine> = None;
+	let mut distance = std::f64::INFINITY;
+	for line in lines.flatten() {
+		let d = tolerance.line_distance(&line.0, target);
+		if d < distance { distance = d; nearest = Some(line); }
+	}
+	return nearest;
+}
+
+// the cheapest way found to construct something: (depth, number of steps),
+// and which of its origins to use. None if no way has been found yet, or it
+// can't be made from the initial geometry. the number of steps adds up the
+// parents' steps, so a step two parents share is counted twice: it's an upper
+// bound on the steps of the sequence, which lists each step once
+type Cost = Option<(usize, usize, usize)>;
+
+// origins can make cycles (with provenance "all", a line can be listed as
+// made from a point which was made from that line). the costs are found by
+// relaxing every node until nothing improves, so they don't depend on the
+// order anything is visited in, and a cycle is never a way to make something
+struct Search<'a, P: 'a, L: 'a> {
+	points: &'a P,
+	lines: &'a L,
+	// the key scales of the points' and the lines' tolerances
+	point_scale: f64,
+	line_scale: f64,
+	// every point and line the search has reached, and its constructions.
+	// None if it isn't in the grid or container
+	nodes: HashMap<Key, Option<&'a Vec<Origin>>>,
+	// the keys of nodes, in the order they were reached
+	order: Vec<Key>,
+	costs: HashMap<Key, Cost>,
+}
+
+impl<'a, P: PointIndex, L: LineIndex> Search<'a, P, L> {
+	fn node_key (&self, point: Option<&Vector>, line: Option<&Line>) -> Key {
+		match point {
+			Some(p) => point_key(p, self.point_scale),
+			None => line_key(line.unwrap(), self.line_scale),
+		}
+	}
+	fn origins (&self, key: &Key, point: Option<&Vector>, line: Option<&Line>) -> Option<&'a Vec<Origin>> {
+		match *key {
+			Key::Point(..) => self.points.find_origins(point.unwrap()),
+			Key::Line(..) => self.lines.find_origins(line.unwrap()),
+		}
+	}
+	// every point and line the target is made from, through any origin
+	fn reach (&mut self, point: Option<&Vector>, line: Option<&Line>) {
+		let mut stack: Vec<(Option<Vector>, Option<Line>)> = vec![(point.cloned(), line.cloned())];
+		while let Some((point, line)) = stack.pop() {
+			let key = self.node_key(point.as_ref(), line.as_ref());
+			if self.nodes.contains_key(&key) { continue }
+			let origins = self.origins(&key, point.as_ref(), line.as_ref());
+			for origin in origins.into_iter().flat_map(|origins| origins.iter()) {
+				for p in origin.points.iter() { stack.push((Some(*p), None)); }
+				for l in origin.lines.iter() { stack.push((None, Some(*l))); }
+			}
+			self.nodes.insert(key, origins);
+			self.order.push(key);
+		}
+	}
+	// the cheapest origin, from the costs of the parents found so far
+	fn best (&self, key: &Key) -> Cost {
+		let origins = match self.nodes[key] {
+			Some(origins) => origins,
+			None => return None,
+		};
+		if let Some(i) = origins.iter().position(|origin| origin.initial()) {
+			return Some((0, 0, i));
+		}
+		let mut best: Cost = None;
+		for i in 0..origins.len() {
+			let mut depth: usize = 0;
+			let mut steps: usize = 1;
+			let parents = origins[i].points.iter().map(|p| point_key(p, self.point_scale))
+				.chain(origins[i].lines.iter().map(|l| line_key(l, self.line_scale)));
+			let mut possible = true;
+			for parent in parents {
+				match self.costs.get(&parent).cloned().unwrap_or(None) {
+					Some(c) => { depth = std::cmp::max(depth, c.0); steps += c.1; }
+					None => { possible = false; break }
+				}
+			}
+			if !possible { continue }
+			// folding a line takes a round, crossing two lines does not
+			if origins[i].axiom != 0 { depth += 1; }
+			let better = match best {
+				Some(b) => depth < b.0 || (depth == b.0 && steps < b.1),
+				None => true,
+			};
+			if better { best = Some((depth, steps, i)); }
+		}
+		return best;
+	}
+	// costs only go down, and stop once no origin of any node is cheaper.
+	// then every node takes its first cheapest origin
+	fn relax (&mut self) {
+		let order = self.order.clone();
+		let mut changed = true;
+		while changed {
+			changed = false;
+			for key in order.iter() {
+				let best = match self.best(key) {
+					Some(best) => best,
+					None => continue,
+				};
+				let better = match self.costs.get(key).cloned().unwrap_or(None) {
+					Some(c) => best.0 < c.0 || (best.0 == c.0 && best.1 < c.1),
+					None => true,
+				};
+				if better { self.costs.insert(*key, Some(best)); changed = true; }
+			}
+		}
+		for key in order.iter() {
+			let best = self.best(key);
+			self.costs.insert(*key, best);
+		}
+	}
+	fn cost (&mut self, point: Option<&Vector>, line: Option<&Line>) -> Cost {
+		self.reach(point, line);
+		self.relax();
+		return self.costs[&self.node_key(point, line)];
+	}
+	// depth first, parents are listed before the step that uses them
+	fn collect (&self, point: Option<&Vector>, line: Option<&Line>, steps: &mut Vec<Step>, done: &mut Vec<Key>) {
+		let key = self.node_key(point, line);
+		if done.contains(&key) { return }
+		done.push(key);
+		let (depth, _, index) = match self.costs.get(&key) {
+			Some(&Some(cost)) => cost,
+			_ => return,
+		};
+		let origin = &self.nodes[&key].unwrap()[index];
+		if origin.initial() { return }
+		for p in origin.points.iter() { self.collect(Some(p), None, steps, done); }
+		for l in origin.lines.iter() { self.collect(None, Some(l), steps, done); }
+		steps.push(Step {
+			axiom: origin.axiom,
+			points: origin.points.clone(),
+			lines: origin.lines.clone(),
+			point: point.cloned(),
+			line: line.cloned(),
+			depth,
+		});
+	}
+}
+
+fn sequence<P: PointIndex, L: LineIndex> (
+	points: &P,
+	lines: &L,
+	point: Option<&Vector>,
+	line: Option<&Line>
+) -> Option<Vec<Step>> {
+	if !points.provenance().tracking() || !lines.provenance().tracking() { return None }
+	let mut search = Search {
+		points,
+		lines,
+		point_scale: key_scale(&points.tolerance()),
+		line_scale: key_scale(&lines.tolerance()),
+		nodes: HashMap::new(),
+		order: Vec::new(),
+		costs: HashMap::new(),
+	};
+	search.cost(point, line)?;
+	let mut steps: Vec<Step> = Vec::new();
+	search.collect(point, line, &mut steps, &mut Vec::new());
+	return Some(steps);
+}
+
+// the shallowest, then shortest (see Cost), sequence of folds from the initial
+// geometry which makes a point already in the index. an empty list if the
+// point is part of the initial geometry. None if provenance isn't being tracked.
+pub fn point_sequence<P: PointIndex, L: LineIndex> (points: &P, lines: &L, point: &Vector) -> Option<Vec<Step>> {
+	return sequence(points, lines, Some(point), None);
+}
+
+// same as point_sequence, for a line already in the index
+pub fn line_sequence<P: PointIndex, L: LineIndex> (points: &P, lines: &L, line: &Line) -> Option<Vec<Step>> {
+	return sequence(points, lines, None, Some(line));
+}
+
+pub fn format_point (point: &Vector) -> String {
+	format!("({:.6}, {:.6})", point.x, point.y)
+}
+
+pub fn format_line (line: &Line) -> String {
+	format!("[u ({:.6}, {:.6}) d {:.6}]", line.u.x, line.u.y, line.d)
+}
+
+pub fn format_step (step: &Step) -> String {
+	let inputs: Vec<String> = step.points.iter().map(|p| format_point(p))
+		.chain(step.lines.iter().map(|l| format_line(l)))
+		.collect();
+	let result = match (step.point, step.line) {
+		(Some(p), _) => format!("point {}", format_point(&p)),
+		(_, Some(l)) => format!("line {}", format_line(&l)),
+		_ => String::new(),
+	};
+	if step.axiom == 0 {
+		return format!("round {}: intersect {} => {}", step.depth, inputs.join(" and "), result);
+	}
+	return format!("round {}: axiom {} from {} => {}", step.depth, step.axiom, inputs.join(", "), result);
+}
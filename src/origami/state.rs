@@ -0,0 +1,258 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::io::BufWriter;
+use rabbit_ear as ear;
+use self::ear::Vector;
+use self::ear::Line;
+use origami::GridVec;
+use origami::PointIndex;
+use origami::make_tracked_grid;
+use origami::LineContainer;
+use origami::make_tracked_line_container;
+use origami::LineIndex;
+use origami::Origin;
+use origami::Provenance;
+use origami::Polygon;
+use origami::Tolerance;
+use origami::tolerance::make_tolerance;
+use origami::polygon::make_unit_square;
+use origami::polygon::make_polygon;
+use origami::round::MAX_ROUNDS;
+
+// everything needed to continue an exploration, saved after each round.
+// the file is little endian:
+//   magic "AXST", version u32
+//   rounds u32 (rounds already made), provenance u8 (0 none, 1 first, 2 all),
+//   tolerance f64 (the points' and lines' epsilon)
+//   paper: square u8, vertex count u32, vertices (x f64, y f64),
+//     offset (x f64, y f64), scale f64
+//   points: count u64, then each x f64, y f64, count u64, round u8, [origins]
+//   lines: count u64, then each u.x f64, u.y f64, d f64, count u64,
+//     axiom u8, round u8, [origins]
+// origins are only written when provenance is tracked: count u32, then each
+// axiom u8, point count u32, points, line count u32, lines.
+// the entries are in container order, and their origins are restored as
+// they were saved, so a resumed run is the same as one which never stopped.
+const MAGIC: &[u8; 4] = b"AXST";
+const VERSION: u32 = 3;
+
+pub struct State {
+	pub rounds: usize,
+	pub paper: Polygon,
+	pub points: GridVec,
+	pub lines: LineContainer,
+}
+
+// a new exploration, the paper's corners and sides are the initial geometry
+pub fn make_state (paper: Polygon, provenance: Provenance) -> State {
+	let mut points: GridVec = make_tracked_grid(provenance);
+	let mut lines: LineContainer = make_tracked_line_container(provenance);
+	paper.vertices.iter().for_each(|vertex| points.push(*vertex));
+	paper.sides.iter().for_each(|side| lines.push(*side, 0));
+	return State { rounds: 0, paper, points, lines };
+}
+
+impl State {
+	// the points and lines are refiled if the tolerance is a different one,
+	// which is returned
+	pub fn set_tolerance (&mut self, tolerance: Tolerance) -> Option<Tolerance> {
+		let old = self.points.tolerance();
+		if tolerance == old { return None }
+		self.points.set_tolerance(tolerance);
+		self.lines.set_tolerance(tolerance);
+		return Some(old);
+	}
+}
+
+fn invalid (message: String) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_u8 (w: &mut dyn Write, value: u8) -> io::Result<()> { w.write_all(&[value]) }
+fn write_u32 (w: &mut dyn Write, value: u32) -> io::Result<()> { w.write_all(&value.to_le_bytes()) }
+fn write_u64 (w: &mut dyn Write, value: u64) -> io::Result<()> { w.write_all(&value.to_le_bytes()) }
+fn write_f64 (w: &mut dyn Write, value: f64) -> io::Result<()> { w.write_all(&value.to_bits().to_le_bytes()) }
+
+fn read_u8 (r: &mut dyn Read) -> io::Result<u8> {
+	let mut bytes = [0; 1];
+	r.read_exact(&mut bytes)?;
+	return Ok(bytes[0]);
+}
+fn read_u32 (r: &mut dyn Read) -> io::Result<u32> {
+	let mut bytes = [0; 4];
+	r.read_exact(&mut bytes)?;
+	return Ok(u32::from_le_bytes(bytes));
+}
+fn read_u64 (r: &mut dyn Read) -> io::Result<u64> {
+	let mut bytes = [0; 8];
+	r.read_exact(&mut bytes)?;
+	return Ok(u64::from_le_bytes(bytes));
+}
+fn read_f64 (r: &mut dyn Read) -> io::Result<f64> {
+	return Ok(f64::from_bits(read_u64(r)?));
+}
+
+fn write_vector (w: &mut dyn Write, v: &Vector) -> io::Result<()> {
+	write_f64(w, v.x)?;
+	write_f64(w, v.y)
+}
+fn write_line (w: &mut dyn Write, l: &Line) -> io::Result<()> {
+	write_vector(w, &l.u)?;
+	write_f64(w, l.d)
+}
+fn read_vector (r: &mut dyn Read) -> io::Result<Vector> {
+	return Ok(Vector { x: read_f64(r)?, y: read_f64(r)? });
+}
+fn read_line (r: &mut dyn Read) -> io::Result<Line> {
+	return Ok(Line { u: read_vector(r)?, d: read_f64(r)? });
+}
+
+fn write_origins (w: &mut dyn Write, origins: &Vec<Origin>) -> io::Result<()> {
+	write_u32(w, origins.len() as u32)?;
+	for origin in origins.iter() {
+		write_u8(w, origin.axiom)?;
+		write_u32(w, origin.points.len() as u32)?;
+		for p in origin.points.iter() { write_vector(w, p)?; }
+		write_u32(w, origin.lines.len() as u32)?;
+		for l in origin.lines.iter() { write_line(w, l)?; }
+	}
+	return Ok(());
+}
+
+fn read_origins (r: &mut dyn Read) -> io::Result<Vec<Origin>> {
+	let count = read_u32(r)?;
+	let mut origins: Vec<Origin> = Vec::new();
+	for _i in 0..count {
+		let axiom = read_u8(r)?;
+		let point_count = read_u32(r)?;
+		let mut points: Vec<Vector> = Vec::new();
+		for _j in 0..point_count { points.push(read_vector(r)?); }
+		let line_count = read_u32(r)?;
+		let mut lines: Vec<Line> = Vec::new();
+		for _j in 0..line_count { lines.push(read_line(r)?); }
+		origins.push(Origin { axiom, points, lines });
+	}
+	return Ok(origins);
+}
+
+fn provenance_tag (provenance: Provenance) -> u8 {
+	match provenance {
+		Provenance::None => 0,
+		Provenance::First => 1,
+		Provenance::All => 2,
+	}
+}
+
+fn write_state<P: PointIndex, L: LineIndex> (
+	w: &mut dyn Write,
+	rounds: usize,
+	paper: &Polygon,
+	points: &P,
+	lines: &L
+) -> io::Result<()> {
+	w.write_all(MAGIC)?;
+	write_u32(w, VERSION)?;
+	write_u32(w, rounds as u32)?;
+	write_u8(w, provenance_tag(points.provenance()))?;
+	write_f64(w, points.tolerance().epsilon)?;
+	write_u8(w, paper.square as u8)?;
+	write_u32(w, paper.vertices.len() as u32)?;
+	for v in paper.vertices.iter() { write_vector(w, v)?; }
+	write_vector(w, &paper.offset)?;
+	write_f64(w, paper.scale)?;
+	write_u64(w, points.len() as u64)?;
+	for point in points.flatten() {
+		write_vector(w, &point.0)?;
+		write_u64(w, point.1)?;
+		write_u8(w, point.2)?;
+		if points.provenance().tracking() { write_origins(w, points.find_origins(&point.0).unwrap())?; }
+	}
+	write_u64(w, lines.len() as u64)?;
+	for line in lines.flatten() {
+		write_line(w, &line.0)?;
+		write_u64(w, line.1)?;
+		write_u8(w, line.2)?;
+		write_u8(w, line.3)?;
+		if lines.provenance().tracking() { write_origins(w, lines.find_origins(&line.0).unwrap())?; }
+	}
+	return w.flush();
+}
+
+// "rounds" is the number of rounds made so far. the file is written next to
+// the destination and then moved over it, an interrupted save never leaves
+// a broken state behind
+pub fn save_state<P: PointIndex, L: LineIndex> (
+	path: &str,
+	rounds: usize,
+	paper: &Polygon,
+	points: &P,
+	lines: &L
+) -> io::Result<()> {
+	let partial = format!("{}.partial", path);
+	{
+		let mut writer = BufWriter::new(File::create(&partial)?);
+		write_state(&mut writer, rounds, paper, points, lines)?;
+	}
+	return fs::rename(&partial, path);
+}
+
+fn read_state (r: &mut dyn Read) -> io::Result<State> {
+	let mut magic = [0; 4];
+	r.read_exact(&mut magic)?;
+	if &magic != MAGIC { return Err(invalid("not a saved state".to_string())) }
+	let version = read_u32(r)?;
+	if version != VERSION {
+		return Err(invalid(format!("state version {}, expected {}", version, VERSION)));
+	}
+	let rounds = read_u32(r)? as usize;
+	if rounds > MAX_ROUNDS {
+		return Err(invalid(format!("{} rounds, at most {} can be made", rounds, MAX_ROUNDS)));
+	}
+	let provenance = match read_u8(r)? {
+		0 => Provenance::None,
+		1 => Provenance::First,
+		2 => Provenance::All,
+		tag => return Err(invalid(format!("unknown provenance {}", tag))),
+	};
+	let epsilon = read_f64(r)?;
+	let tolerance = make_tolerance(epsilon).ok_or(invalid(format!("the tolerance is {}", epsilon)))?;
+	let square = read_u8(r)? != 0;
+	let vertex_count = read_u32(r)?;
+	let mut vertices: Vec<Vector> = Vec::new();
+	for _i in 0..vertex_count { vertices.push(read_vector(r)?); }
+	let mut paper = if square { make_unit_square() } else {
+		make_polygon(vertices).ok_or(invalid("the paper is not a convex polygon".to_string()))?
+	};
+	// the vertices are already in the unit square, the units they came from are saved
+	paper.offset = read_vector(r)?;
+	paper.scale = read_f64(r)?;
+	if !(paper.scale > 0.0) { return Err(invalid(format!("the paper's scale is {}", paper.scale))) }
+	let mut points: GridVec = make_tracked_grid(provenance);
+	points.set_tolerance(tolerance);
+	let point_count = read_u64(r)?;
+	for _i in 0..point_count {
+		let point = (read_vector(r)?, read_u64(r)?, read_u8(r)?);
+		let origins = if provenance.tracking() { read_origins(r)? } else { vec![] };
+		points.push_counted(point, &origins);
+	}
+	let mut lines: LineContainer = make_tracked_line_container(provenance);
+	lines.set_tolerance(tolerance);
+	let line_count = read_u64(r)?;
+	for _i in 0..line_count {
+		let line = (read_line(r)?, read_u64(r)?, read_u8(r)?, read_u8(r)?);
+		let origins = if provenance.tracking() { read_origins(r)? } else { vec![] };
+		lines.push_counted(line, &origins);
+	}
+	return Ok(State { rounds, paper, points, lines });
+}
+
+pub fn load_state (path: &str) -> io::Result<State> {
+	let mut reader = BufReader::new(File::open(path)?);
+	return read_state(&mut reader).map_err(|error| match error.kind() {
+		io::ErrorKind::UnexpectedEof => invalid("the file ends early".to_string()),
+		_ => error,
+	});
+}
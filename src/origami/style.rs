@@ -0,0 +1,191 @@
+// how the svg images look
+
+// how a count becomes an opacity, relative to the largest count
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Curve {
+	Power(f64), // (count / max) ^ exponent
+	Log, // log(1 + count) / log(1 + max)
+	Linear, // count / max
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ColorBy {
+	None, // the theme's foreground
+	Count, // a colormap from dark (rare) to bright (common)
+	Axiom, // the axiom which first made a line. points are all the foreground
+	Round, // the round a point or line first appears in
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Theme {
+	Dark, // white on black
+	Light, // black on white
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Style {
+	pub curve: Curve,
+	pub color_by: ColorBy,
+	pub theme: Theme,
+	// the larger side of the image, in pixels
+	pub size: f64,
+	// in the paper's units, the paper fits inside the unit square
+	pub stroke_width: f64,
+	pub radius: f64,
+}
+
+pub fn make_style () -> Style {
+	Style {
+		curve: Curve::Power(0.1),
+		color_by: ColorBy::None,
+		theme: Theme::Dark,
+		size: 907.0,
+		stroke_width: 0.0002,
+		radius: 0.001,
+	}
+}
+
+// one color for each round after the initial geometry, which is the foreground.
+// rounds past the end of the list reuse the last color
+const ROUND_COLORS: [&str; 5] = ["#ff4136", "#ffdc00", "#2ecc40", "#0074d9", "#b10dc9"];
+
+// axioms 1 to 7, the paper's sides (0) are the foreground
+const AXIOM_COLORS: [&str; 7] = ["#e6194b", "#3cb44b", "#ffe119", "#4363d8", "#f58231", "#911eb4", "#42d4f4"];
+
+// the count colormap, evenly spaced from 0 to 1 (magma)
+const COUNT_COLORS: [(f64, f64, f64); 5] = [
+	(59.0, 15.0, 112.0),
+	(140.0, 41.0, 129.0),
+	(222.0, 73.0, 104.0),
+	(254.0, 159.0, 109.0),
+	(252.0, 253.0, 191.0),
+];
+
+// "power:EXPONENT", "log" or "linear"
+pub fn parse_curve (string: &str) -> Option<Curve> {
+	let parts: Vec<&str> = string.splitn(2, ':').collect();
+	match (parts[0], parts.get(1)) {
+		("power", Some(exponent)) => exponent.parse::<f64>().ok()
+			.filter(|exponent| *exponent > 0.0)
+			.map(Curve::Power),
+		("log", None) => Some(Curve::Log),
+		("linear", None) => Some(Curve::Linear),
+		_ => None,
+	}
+}
+
+pub fn parse_color_by (string: &str) -> Option<ColorBy> {
+	match string {
+		"none" => Some(ColorBy::None),
+		"count" => Some(ColorBy::Count),
+		"axiom" => Some(ColorBy::Axiom),
+		"round" => Some(ColorBy::Round),
+		_ => None,
+	}
+}
+
+pub fn parse_theme (string: &str) -> Option<Theme> {
+	match string {
+		"dark" => Some(Theme::Dark),
+		"light" => Some(Theme::Light),
+		_ => None,
+	}
+}
+
+fn count_color (t: f64) -> String {
+	let t = t.max(0.0).min(1.0) * (COUNT_COLORS.len() - 1) as f64;
+	let i = std::cmp::min(t.floor() as usize, COUNT_COLORS.len() - 2);
+	let f = t - i as f64;
+	let (a, b) = (COUNT_COLORS[i], COUNT_COLORS[i + 1]);
+	let mix = |a: f64, b: f64| (a + (b - a) * f).round() as u8;
+	return format!("#{:02x}{:02x}{:02x}", mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2));
+}
+
+impl Style {
+	// the opacity of something made "count" times, when the most common is "max".
+	// with nothing counted (max is 0) everything is opaque
+	pub fn opacity (&self, count: u64, max: u64) -> f64 {
+		if max == 0 { return 1.0 }
+		let (count, max) = (count as f64, max as f64);
+		match self.curve {
+			Curve::Power(exponent) => (count / max).powf(exponent),
+			Curve::Log => (1.0 + count).ln() / (1.0 + max).ln(),
+			Curve::Linear => count / max,
+		}
+	}
+	pub fn background (&self) -> &'static str {
+		match self.theme { Theme::Dark => "black", Theme::Light => "white" }
+	}
+	pub fn foreground (&self) -> &'static str {
+		match self.theme { Theme::Dark => "white", Theme::Light => "black" }
+	}
+	// None when everything is the foreground color
+	pub fn color (&self, count: u64, max: u64, axiom: u8, round: u8) -> Option<String> {
+		match self.color_by {
+			ColorBy::None => None,
+			ColorBy::Count => Some(count_color(self.opacity(count, max))),
+			ColorBy::Axiom => Some(self.axiom_color(axiom).to_string()),
+			ColorBy::Round => Some(if round == 0 { self.foreground() } else {
+				ROUND_COLORS[std::cmp::min(round as usize - 1, ROUND_COLORS.len() - 1)]
+			}.to_string()),
+		}
+	}
+	pub fn axiom_color (&self, axiom: u8) -> &'static str {
+		if axiom < 1 || axiom > 7 { return self.foreground() }
+		return AXIOM_COLORS[axiom as usize - 1];
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn with_curve (curve: Curve) -> Style {
+		return Style { curve, ..make_style() };
+	}
+
+	fn close (a: f64, b: f64) -> bool { (a - b).abs() < 1e-12 }
+
+	#[test]
+	fn power () {
+		let style = with_curve(Curve::Power(0.5));
+		assert!(close(style.opacity(1, 4), 0.5));
+		assert!(close(style.opacity(4, 4), 1.0));
+		assert!(close(style.opacity(0, 4), 0.0));
+		assert!(close(with_curve(Curve::Power(2.0)).opacity(1, 2), 0.25));
+	}
+
+	#[test]
+	fn log () {
+		let style = with_curve(Curve::Log);
+		assert!(close(style.opacity(1, 3), 0.5));
+		assert!(close(style.opacity(3, 3), 1.0));
+		assert!(close(style.opacity(0, 3), 0.0));
+	}
+
+	#[test]
+	fn linear () {
+		let style = with_curve(Curve::Linear);
+		assert!(close(style.opacity(1, 4), 0.25));
+		assert!(close(style.opacity(4, 4), 1.0));
+		assert!(close(style.opacity(0, 4), 0.0));
+	}
+
+	#[test]
+	fn nothing_counted () {
+		for curve in [Curve::Power(0.1), Curve::Log, Curve::Linear].iter() {
+			let style = with_curve(*curve);
+			assert_eq!(style.opacity(0, 0), 1.0);
+			assert_eq!(Style { color_by: ColorBy::Count, ..style }.color(0, 0, 1, 1), Some("#fcfdbf".to_string()));
+		}
+	}
+
+	#[test]
+	fn parse_curves () {
+		assert_eq!(parse_curve("power:0.25"), Some(Curve::Power(0.25)));
+		assert_eq!(parse_curve("power:0"), None);
+		assert_eq!(parse_curve("log"), Some(Curve::Log));
+		assert_eq!(parse_curve("linear"), Some(Curve::Linear));
+		assert_eq!(parse_curve("linear:2"), None);
+	}
+}
@@ -0,0 +1,96 @@
+use std::cmp::Ordering;
+use rabbit_ear as ear;
+use self::ear::Segment;
+use origami::CountPoint;
+use origami::CountLine;
+use origami::Polygon;
+use origami::sequence::canonical_line;
+
+// tables of the drawn points and lines, as CSV or JSON.
+// points: x, y, count, round
+// lines: the normal form u.x, u.y, d, the clipped segment x1, y1, x2, y2,
+// count, axiom, round
+// coordinates are in the paper's units, like the ones --find-point takes and
+// prints, not the unit square the paper is fitted into to draw.
+// numbers are written exactly (the shortest text which reads back the same).
+// rows are sorted by position, so the same geometry always makes the same table
+
+// -0 is written as 0
+fn number (value: f64) -> f64 { if value == 0.0 { 0.0 } else { value } }
+
+// the file extension picks the format, CSV unless it ends in .json
+pub fn is_json (file: &str) -> bool { file.to_lowercase().ends_with(".json") }
+
+fn compare (a: &[f64], b: &[f64]) -> Ordering {
+	for i in 0..a.len() {
+		match a[i].partial_cmp(&b[i]) {
+			Some(Ordering::Equal) | None => continue,
+			Some(order) => return order,
+		}
+	}
+	return Ordering::Equal;
+}
+
+fn sorted_points (points: &Vec<CountPoint>, paper: &Polygon) -> Vec<CountPoint> {
+	let mut list: Vec<CountPoint> = points.iter().map(|el| (paper.to_paper(el.0), el.1, el.2)).collect();
+	list.sort_by(|a, b| compare(&[a.0.x, a.0.y], &[b.0.x, b.0.y]));
+	return list;
+}
+
+// the lines which cross the paper, with their segment. the segment starts at
+// its end with the smaller x (or y, if vertical)
+fn sorted_lines (lines: &Vec<CountLine>, paper: &Polygon) -> Vec<(CountLine, Segment)> {
+	let mut list: Vec<(CountLine, Segment)> = Vec::new();
+	for i in 0..lines.len() {
+		let (success, segment) = paper.clip(lines[i].0);
+		if !success { continue }
+		let segment = Segment { a: paper.to_paper(segment.a), b: paper.to_paper(segment.b) };
+		let forward = compare(&[segment.a.x, segment.a.y], &[segment.b.x, segment.b.y]) != Ordering::Greater;
+		let segment = if forward { segment } else { Segment { a: segment.b, b: segment.a } };
+		let line = canonical_line(&paper.line_to_paper(lines[i].0));
+		list.push(((line, lines[i].1, lines[i].2, lines[i].3), segment));
+	}
+	list.sort_by(|a, b| compare(
+		&[(a.0).0.u.x, (a.0).0.u.y, (a.0).0.d],
+		&[(b.0).0.u.x, (b.0).0.u.y, (b.0).0.d]));
+	return list;
+}
+
+pub fn points_csv (points: &Vec<CountPoint>, paper: &Polygon) -> String {
+	let mut table: String = String::new();
+	table.push_str("x,y,count,round\n");
+	for point in sorted_points(points, paper).iter() {
+		table.push_str(&format!("{},{},{},{}\n", number(point.0.x), number(point.0.y), point.1, point.2));
+	}
+	return table;
+}
+
+pub fn points_json (points: &Vec<CountPoint>, paper: &Polygon) -> String {
+	let rows: Vec<String> = sorted_points(points, paper).iter()
+		.map(|point| format!("{{\"x\": {}, \"y\": {}, \"count\": {}, \"round\": {}}}",
+			number(point.0.x), number(point.0.y), point.1, point.2))
+		.collect();
+	return format!("[\n{}\n]\n", rows.join(",\n"));
+}
+
+pub fn lines_csv (lines: &Vec<CountLine>, paper: &Polygon) -> String {
+	let mut table: String = String::new();
+	table.push_str("ux,uy,d,x1,y1,x2,y2,count,axiom,round\n");
+	for &(line, segment) in sorted_lines(lines, paper).iter() {
+		table.push_str(&format!("{},{},{},{},{},{},{},{},{},{}\n",
+			number(line.0.u.x), number(line.0.u.y), number(line.0.d),
+			number(segment.a.x), number(segment.a.y), number(segment.b.x), number(segment.b.y),
+			line.1, line.2, line.3));
+	}
+	return table;
+}
+
+pub fn lines_json (lines: &Vec<CountLine>, paper: &Polygon) -> String {
+	let rows: Vec<String> = sorted_lines(lines, paper).iter()
+		.map(|&(line, segment)| format!("{{\"ux\": {}, \"uy\": {}, \"d\": {}, \"x1\": {}, \"y1\": {}, \"x2\": {}, \"y2\": {}, \"count\": {}, \"axiom\": {}, \"round\": {}}}",
+			number(line.0.u.x), number(line.0.u.y), number(line.0.d),
+			number(segment.a.x), number(segment.a.y), number(segment.b.x), number(segment.b.y),
+			line.1, line.2, line.3))
+		.collect();
+	return format!("[\n{}\n]\n", rows.join(",\n"));
+}
@@ -0,0 +1,90 @@
+use std::f64::consts::PI;
+use std::f64::consts::SQRT_2;
+use rabbit_ear as ear;
+use self::ear::Vector;
+use self::ear::Line;
+
+// the same as rabbit-ear's equivalent
+pub const DEFAULT_TOLERANCE: f64 = 1.0e-10;
+// anything looser leaves too few cells to be worth indexing
+pub const MAX_TOLERANCE: f64 = 0.01;
+// the finest cells the indices use: points per unit length, lines across
+// the half turn of normal angles and across the unit square's diagonal
+const POINT_CELLS: f64 = 1000.0;
+const LINE_CELLS: f64 = 10000.0;
+
+// how close two points, or two lines, are to be the same. points are the
+// same if both coordinates are less than "epsilon" apart, lines if their
+// normals and distances are (or one's are the other's flipped).
+// the indices size their cells from it: a search covers half a cell around
+// a point or line, which has to reach twice the tolerance to see near misses
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Tolerance {
+	pub epsilon: f64,
+}
+
+// how many lookups found a match ("merges"), how many of those were more
+// than half the tolerance away ("close_merges"), and how many lookups found
+// no match, but something less than twice the tolerance away ("close_misses").
+// the closer to the edge, the more a different tolerance changes the result
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct EdgeCount {
+	pub merges: u64,
+	pub close_merges: u64,
+	pub close_misses: u64,
+}
+
+pub fn make_tolerance (epsilon: f64) -> Option<Tolerance> {
+	if !(epsilon > 0.0 && epsilon <= MAX_TOLERANCE) { return None }
+	return Some(Tolerance { epsilon });
+}
+
+pub fn default_tolerance () -> Tolerance {
+	return Tolerance { epsilon: DEFAULT_TOLERANCE };
+}
+
+impl Tolerance {
+	// the largest difference between the coordinates
+	pub fn point_distance (&self, a: &Vector, b: &Vector) -> f64 {
+		return (a.x - b.x).abs().max((a.y - b.y).abs());
+	}
+	// the largest difference between the normals and distances, with one
+	// line flipped if that's closer
+	pub fn line_distance (&self, a: &Line, b: &Line) -> f64 {
+		let same = (a.u.x - b.u.x).abs().max((a.u.y - b.u.y).abs()).max((a.d - b.d).abs());
+		let flip = (a.u.x + b.u.x).abs().max((a.u.y + b.u.y).abs()).max((a.d + b.d).abs());
+		return same.min(flip);
+	}
+	pub fn within (&self, distance: f64) -> bool { distance < self.epsilon }
+	// cells per unit length of the point indices
+	pub fn point_cells (&self) -> f64 {
+		return POINT_CELLS.min(1.0 / (4.0 * self.epsilon));
+	}
+	// cells per radian of the line index. normals epsilon apart in both
+	// coordinates are up to sqrt(2) epsilon apart in angle
+	pub fn angle_cells (&self) -> f64 {
+		return (LINE_CELLS / PI).min(1.0 / (4.0 * SQRT_2 * self.epsilon));
+	}
+	// cells per unit distance of the line index
+	pub fn distance_cells (&self) -> f64 {
+		return (LINE_CELLS / SQRT_2).min(1.0 / (4.0 * self.epsilon));
+	}
+}
+
+impl EdgeCount {
+	// one lookup, "nearest" is how far the match (or the closest entry, if
+	// none matched) was. infinite if there was nothing nearby
+	pub fn tally (&mut self, tolerance: &Tolerance, nearest: f64) {
+		if tolerance.within(nearest) {
+			self.merges += 1;
+			if nearest >= 0.5 * tolerance.epsilon { self.close_merges += 1; }
+		} else if nearest < 2.0 * tolerance.epsilon {
+			self.close_misses += 1;
+		}
+	}
+	pub fn add (&mut self, other: &EdgeCount) {
+		self.merges += other.merges;
+		self.close_merges += other.close_merges;
+		self.close_misses += other.close_misses;
+	}
+}
@@ -0,0 +1,299 @@
+extern crate origami_axioms;
+
+use origami_axioms::exact;
+use origami_axioms::exact::ExactPoint;
+use origami_axioms::exact::Made;
+use origami_axioms::exact::ExactState;
+use origami_axioms::exact::make_exact_state;
+use origami_axioms::exact::make_rational;
+use origami_axioms::exact::integer;
+use origami_axioms::exact::fraction;
+use origami_axioms::exact::make_surd;
+use origami_axioms::math::Vector;
+use origami_axioms::make_round;
+use origami_axioms::make_round_plan;
+use origami_axioms::make_state;
+use origami_axioms::Provenance;
+use origami_axioms::polygon::make_unit_square;
+use origami_axioms::polygon::make_regular_polygon;
+use origami_axioms::polygon::make_rectangle;
+use origami_axioms::polygon::Polygon;
+use origami_axioms::PointIndex;
+use origami_axioms::LineIndex;
+
+fn point (x: (i128, i128), y: (i128, i128)) -> ExactPoint {
+	let (x, y) = (make_rational(x.0, x.1).unwrap(), make_rational(y.0, y.1).unwrap());
+	return ExactPoint { x: fraction(x), y: fraction(y) };
+}
+
+// a + b sqrt(r), with whole a and b
+fn surd (a: i128, b: i128, r: i128) -> exact::Surd { make_surd(integer(a), integer(b), r) }
+
+// "rounds" rounds of these axioms, with intersections, from the paper
+fn try_explore (paper: &Polygon, axioms: &[u8], rounds: usize) -> Result<ExactState, String> {
+	let mut state = make_exact_state(paper).unwrap();
+	for round in 0..rounds {
+		let mut plan = make_round_plan();
+		plan.axioms = axioms.to_vec();
+		plan.intersections = true;
+		exact::make_round(round, &plan, &mut state)?;
+	}
+	return Ok(state);
+}
+
+fn explore_paper (paper: &Polygon, axioms: &[u8], rounds: usize) -> ExactState {
+	return try_explore(paper, axioms, rounds).unwrap();
+}
+
+fn explore (axioms: &[u8], rounds: usize) -> ExactState {
+	return explore_paper(&make_unit_square(), axioms, rounds);
+}
+
+// the exact points and lines are the ones floating point makes, with the
+// same counts, axioms and rounds
+fn assert_same_as_floating_point (paper: &Polygon, axioms: &[u8], rounds: usize) {
+	let state = explore_paper(paper, axioms, rounds);
+	let (points, lines) = state.to_containers();
+	let mut float = make_state(paper.clone(), Provenance::None);
+	let mut plan = make_round_plan();
+	plan.axioms = axioms.to_vec();
+	plan.intersections = true;
+	for round in 0..rounds {
+		make_round(round, &plan, &mut float.points, &mut float.lines, &float.paper);
+	}
+	assert_eq!(points.len(), float.points.len());
+	assert_eq!(lines.len(), float.lines.len());
+	for line in float.lines.flatten() {
+		let found = lines.flatten().into_iter().find(|el| el.0.equivalent(line.0)).unwrap();
+		assert_eq!((found.1, found.2, found.3), (line.1, line.2, line.3));
+	}
+	for point in float.points.flatten() {
+		let found = points.flatten().into_iter().find(|el| el.0.equivalent(point.0)).unwrap();
+		assert_eq!((found.1, found.2), (point.1, point.2));
+	}
+}
+
+#[test]
+fn fractions_are_reduced () {
+	let half = make_rational(3, 6).unwrap();
+	assert_eq!((half.n, half.d), (1, 2));
+	let negative = make_rational(2, -4).unwrap();
+	assert_eq!((negative.n, negative.d), (-1, 2));
+	assert!(make_rational(1, 0).is_none());
+	let sum = half.add(&make_rational(1, 3).unwrap()).unwrap();
+	assert_eq!(format!("{}", sum), "5/6");
+	assert_eq!(format!("{}", sum.sub(&sum).unwrap()), "0");
+	assert!(half.div(&integer(0)).is_none());
+	assert_eq!(exact::from_f64(0.375), make_rational(3, 8));
+	assert_eq!(exact::from_f64(0.1), make_rational(1, 10));
+	assert_eq!(exact::from_f64(1.0 / 1.5), make_rational(2, 3));
+	assert!(exact::from_f64(std::f64::consts::PI).is_none());
+}
+
+#[test]
+fn surds () {
+	let one = surd(1, 0, 0);
+	let silver = surd(1, 1, 2);
+	// (1 + sqrt 2)(1 - sqrt 2) = -1, a fraction again
+	let product = silver.mul(&surd(1, -1, 2)).unwrap();
+	assert_eq!(product, surd(-1, 0, 0));
+	assert_eq!(product.r, 0);
+	assert_eq!(one.div(&silver).unwrap(), surd(-1, 1, 2));
+	assert_eq!(surd(1, -1, 2).signum(), Some(-1));
+	assert_eq!(surd(-1, 1, 2).signum(), Some(1));
+	assert_eq!(format!("{}", surd(1, -1, 2)), "1-sqrt(2)");
+	assert_eq!(format!("{}", make_surd(make_rational(1, 2).unwrap(), make_rational(1, 2).unwrap(), 3)), "1/2+1/2*sqrt(3)");
+	assert_eq!(format!("{}", surd(0, 2, 3)), "2*sqrt(3)");
+	// numbers of different fields don't mix
+	assert!(silver.add(&surd(0, 1, 3)).is_none());
+	assert_eq!(exact::surd_from_f64(3_f64.sqrt() / 2.0, 3), Some(make_surd(integer(0), make_rational(1, 2).unwrap(), 3)));
+	assert!(exact::surd_from_f64(3_f64.sqrt(), 2).is_none());
+}
+
+#[test]
+fn square_roots () {
+	// 3 + 2 sqrt 2 = (1 + sqrt 2)^2
+	assert_eq!(surd(3, 2, 2).sqrt(2), Made::Some(surd(1, 1, 2)));
+	assert_eq!(surd(2, 0, 0).sqrt(2), Made::Some(surd(0, 1, 2)));
+	assert_eq!(surd(8, 0, 0).sqrt(2), Made::Some(surd(0, 2, 2)));
+	assert_eq!(surd(9, 0, 0).sqrt(2), Made::Some(surd(3, 0, 0)));
+	assert_eq!(surd(-1, 0, 0).sqrt(2), Made::None);
+	assert_eq!(surd(3, 0, 0).sqrt(2), Made::Outside);
+	// sqrt(2 + sqrt 2) is the next field up
+	assert_eq!(surd(2, 1, 2).sqrt(2), Made::Outside);
+}
+
+#[test]
+fn overflow_is_none () {
+	let big = integer(i128::MAX / 2);
+	assert!(big.mul(&integer(4)).is_none());
+	assert!(big.add(&big).is_some());
+	assert!(big.add(&big).unwrap().add(&integer(2)).is_none());
+}
+
+// comparing never overflows, and never rounds: the first two differ by about 2^-254
+#[test]
+fn comparisons_are_exact () {
+	use std::cmp::Ordering;
+	let large = make_rational(i128::MAX, i128::MAX - 1).unwrap();
+	let larger = make_rational(i128::MAX - 1, i128::MAX - 2).unwrap();
+	assert_eq!(large.compare(&larger), Ordering::Less);
+	assert_eq!(larger.compare(&large), Ordering::Greater);
+	assert_eq!(large.compare(&large), Ordering::Equal);
+	assert_eq!(make_rational(-7, 3).unwrap().compare(&make_rational(-5, 2).unwrap()), Ordering::Greater);
+	assert_eq!(make_rational(-1, 3).unwrap().compare(&integer(0)), Ordering::Less);
+	assert_eq!(make_surd(large, integer(1), 2).compare(&make_surd(larger, integer(1), 2)), Some(Ordering::Less));
+	assert_eq!(surd(1, 1, 2).compare(&surd(2, 0, 0)), Some(Ordering::Greater));
+	assert_eq!(surd(1, 1, 2).compare(&surd(3, -1, 2)), Some(Ordering::Greater));
+	// the difference of these doesn't fit
+	let huge = make_surd(integer(i128::MAX), integer(1), 2);
+	assert_eq!(huge.compare(&huge.neg()), None);
+}
+
+// equal lines built different ways are the same struct
+#[test]
+fn lines_have_one_form () {
+	let corner = point((0, 1), (0, 1));
+	let center = point((1, 2), (1, 2));
+	let far = point((1, 1), (1, 1));
+	let diagonal = exact::axiom1(&corner, &center).unwrap();
+	assert_eq!(diagonal, exact::axiom1(&far, &corner).unwrap());
+	// the fold bringing (1, 0) onto (0, 1) is the diagonal
+	let fold = exact::axiom2(&point((1, 1), (0, 1)), &point((0, 1), (1, 1))).unwrap();
+	assert_eq!(fold, diagonal);
+	assert!(exact::axiom1(&center, &center).is_none());
+	// the line through the center perpendicular to the diagonal
+	let across = exact::axiom4(&center, &diagonal).unwrap();
+	assert_eq!(across, exact::axiom1(&point((1, 1), (0, 1)), &point((0, 1), (1, 1))).unwrap());
+	assert_eq!(exact::intersect(&diagonal, &across), Some(center));
+	assert!(exact::intersect(&diagonal, &diagonal).is_none());
+}
+
+#[test]
+fn axiom7_folds_a_point_onto_a_line () {
+	let bottom = exact::axiom1(&point((0, 1), (0, 1)), &point((1, 1), (0, 1))).unwrap();
+	let left = exact::axiom1(&point((0, 1), (0, 1)), &point((0, 1), (1, 1))).unwrap();
+	// (1/4, 3/4) folds down onto the bottom along y = 3/8, perpendicular to the left
+	let crease = exact::axiom7(&point((1, 4), (3, 4)), &bottom, &left).unwrap();
+	let line = crease.to_line();
+	assert!(line.u.equivalent(Vector { x: 0.0, y: 1.0 }) && (line.d - 0.375).abs() < 1.0e-12);
+	// a fold perpendicular to the bottom never brings a point onto it
+	assert!(exact::axiom7(&point((1, 4), (3, 4)), &bottom, &bottom).is_none());
+}
+
+// the bisectors of the bottom and the left are both diagonals, only one
+// crosses the paper. the bisector of the bottom and a diagonal needs sqrt 2
+#[test]
+fn axiom3_folds_a_line_onto_a_line () {
+	let bottom = exact::axiom1(&point((0, 1), (0, 1)), &point((1, 1), (0, 1))).unwrap();
+	let left = exact::axiom1(&point((0, 1), (0, 1)), &point((0, 1), (1, 1))).unwrap();
+	let top = exact::axiom1(&point((0, 1), (1, 1)), &point((1, 1), (1, 1))).unwrap();
+	let diagonal = exact::axiom1(&point((0, 1), (0, 1)), &point((1, 1), (1, 1))).unwrap();
+	match exact::axiom3(&bottom, &left, 2) {
+		Made::Some(lines) => assert!(lines.contains(&diagonal) && lines.len() == 2),
+		other => panic!("{:?}", other),
+	}
+	match exact::axiom3(&bottom, &top, 2) {
+		Made::Some(lines) => {
+			assert_eq!(lines.len(), 1);
+			assert!((lines[0].to_line().d - 0.5).abs() < 1.0e-12);
+		}
+		other => panic!("{:?}", other),
+	}
+	match exact::axiom3(&bottom, &diagonal, 2) {
+		Made::Some(lines) => {
+			assert_eq!(lines.len(), 2);
+			// y = (sqrt 2 - 1) x, the crease at 22.5 degrees
+			let slope = lines.iter().find(|l| l.b.signum() != Some(0) && l.a.neg().div(&l.b).unwrap().signum() == Some(1)).unwrap();
+			assert_eq!(slope.a.neg().div(&slope.b).unwrap(), surd(-1, 1, 2));
+		}
+		other => panic!("{:?}", other),
+	}
+	// without sqrt 2 in the field the diagonal's normal has no length
+	assert_eq!(exact::axiom3(&bottom, &diagonal, 3), Made::Outside);
+}
+
+#[test]
+fn axiom5_folds_a_point_onto_a_line () {
+	let bottom = exact::axiom1(&point((0, 1), (0, 1)), &point((1, 1), (0, 1))).unwrap();
+	// (0, 1) folds onto the bottom through (1/2, 1/2) at (0, 0) and (1, 0)
+	match exact::axiom5(&point((0, 1), (1, 1)), &point((1, 2), (1, 2)), &bottom, 2) {
+		Made::Some(lines) => assert_eq!(lines.len(), 2),
+		other => panic!("{:?}", other),
+	}
+	// through (0, 0) it lands at (1, 0) and (-1, 0)
+	match exact::axiom5(&point((0, 1), (1, 1)), &point((0, 1), (0, 1)), &bottom, 2) {
+		Made::Some(lines) => assert!(lines.contains(&exact::axiom2(&point((0, 1), (1, 1)), &point((1, 1), (0, 1))).unwrap())),
+		other => panic!("{:?}", other),
+	}
+	// the circle around (1/2, 1) through (1/2, 7/8) never reaches the bottom
+	assert_eq!(exact::axiom5(&point((1, 2), (7, 8)), &point((1, 2), (1, 1)), &bottom, 2), Made::None);
+	// (1, 1) through (0, 0) lands at sqrt 2 on the bottom, only in a field with it
+	assert_eq!(exact::axiom5(&point((1, 1), (1, 1)), &point((0, 1), (0, 1)), &bottom, 3), Made::Outside);
+}
+
+// the counts of the float round 1 tests, made exactly
+#[test]
+fn round_1_counts () {
+	let state = explore(&[2], 1);
+	assert_eq!(state.lines_len(), 8);
+	assert_eq!(state.points_len(), 9);
+	let state = explore(&[1], 1);
+	assert_eq!(state.lines_len(), 6);
+	assert_eq!(state.points_len(), 5);
+	assert_eq!(state.largest_denominator(), 2);
+	let state = explore(&[3], 1);
+	assert_eq!(state.lines_len(), 8);
+	assert_eq!(state.points_len(), 9);
+	let state = explore(&[5], 1);
+	assert_eq!(state.lines_len(), 6);
+	assert_eq!(state.points_len(), 5);
+}
+
+// two rounds make the same points and lines as floating point
+#[test]
+fn same_as_floating_point () {
+	assert_same_as_floating_point(&make_unit_square(), &[1, 2, 4, 7], 2);
+	assert_same_as_floating_point(&make_unit_square(), &[1, 2, 3, 4, 5, 7], 1);
+}
+
+// the triangle and hexagon are in the field of sqrt 3, a4 in the field of
+// sqrt 2. a rectangle's ratio is a fraction
+#[test]
+fn surd_papers () {
+	let triangle = make_regular_polygon(3).unwrap();
+	assert_eq!(make_exact_state(&triangle).unwrap().r, 3);
+	assert_same_as_floating_point(&triangle, &[1, 2, 3, 4, 7], 1);
+	assert_same_as_floating_point(&triangle, &[1, 2], 2);
+	let hexagon = make_regular_polygon(6).unwrap();
+	let state = make_exact_state(&hexagon).unwrap();
+	assert_eq!(state.r, 3);
+	assert_eq!(state.vertices[2], ExactPoint { x: surd(1, 0, 0), y: make_surd(integer(0), make_rational(1, 4).unwrap(), 3) });
+	assert_same_as_floating_point(&hexagon, &[1, 2, 3], 1);
+	let a4 = make_rectangle(1.0 / 2_f64.sqrt()).unwrap();
+	assert_eq!(make_exact_state(&a4).unwrap().r, 2);
+	assert_same_as_floating_point(&a4, &[1, 2, 3, 4], 1);
+	let rectangle = make_rectangle(1.5).unwrap();
+	let state = make_exact_state(&rectangle).unwrap();
+	assert_eq!(state.vertices[2].y, fraction(make_rational(2, 3).unwrap()));
+	assert_same_as_floating_point(&rectangle, &[1, 2, 4, 7], 1);
+}
+
+// a fold which needs a root outside the field stops the round, rather than
+// counting without it
+#[test]
+fn lost_folds_are_errors () {
+	let square = make_unit_square();
+	let message = try_explore(&square, &[1, 2, 3, 4, 5, 7], 2).err().unwrap();
+	assert!(message.contains("needs a square root which isn't a + b sqrt(2)"), "{}", message);
+	assert!(try_explore(&square, &[1, 2, 4, 7], 2).is_ok());
+	let message = try_explore(&square, &[1, 6], 1).err().unwrap();
+	assert_eq!(message, "exact arithmetic can't make axiom 6");
+}
+
+// a pentagon's corners need sqrt 5 and the root of something with it
+#[test]
+fn irrational_paper_is_refused () {
+	assert!(make_exact_state(&make_regular_polygon(5).unwrap()).is_none());
+	assert!(make_exact_state(&make_regular_polygon(7).unwrap()).is_none());
+}
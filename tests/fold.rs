@@ -0,0 +1,123 @@
+extern crate origami_axioms;
+
+use origami_axioms::math::Vector;
+use origami_axioms::math::Segment;
+use origami_axioms::origami::fold::fold_json;
+use origami_axioms::CountSegment;
+use origami_axioms::make_round;
+use origami_axioms::make_round_plan;
+use origami_axioms::make_state;
+use origami_axioms::Provenance;
+use origami_axioms::State;
+use origami_axioms::polygon::make_unit_square;
+use origami_axioms::polygon::make_regular_polygon;
+use origami_axioms::tolerance::default_tolerance;
+use origami_axioms::tolerance::make_tolerance;
+use origami_axioms::PointIndex;
+use origami_axioms::LineIndex;
+
+// the number of entries in one of the FOLD file's lists
+fn count (fold: &str, key: &str) -> usize {
+	let start = format!("\"{}\": [", key);
+	let row = fold.lines().find(|row| row.starts_with(&start)).unwrap();
+	let list = row[start.len()..].trim_end_matches(',').trim_end_matches(']');
+	if list.len() == 0 { return 0 }
+	// lists of lists, or of numbers
+	if list.starts_with('[') { return list.matches('[').count() }
+	return list.split(',').count();
+}
+
+fn segments (state: &State) -> Vec<CountSegment> {
+	return state.lines.flatten().iter()
+		.map(|el| (state.paper.clip(el.0), el))
+		.filter(|el| (el.0).0)
+		.map(|el| ((el.0).1, (el.1).1, (el.1).2, (el.1).3))
+		.collect();
+}
+
+// one round of axioms 1 and 2 folds the diagonals and the midlines. they all
+// cross in the center and cut the square into 8 triangles
+fn one_round () -> State {
+	let mut state = make_state(make_unit_square(), Provenance::None);
+	let mut plan = make_round_plan();
+	plan.axioms = vec![1, 2];
+	make_round(0, &plan, &mut state.points, &mut state.lines, &state.paper);
+	return state;
+}
+
+#[test]
+fn one_round_unit_square () {
+	let state = one_round();
+	let segments = segments(&state);
+	assert_eq!(segments.len(), 8);
+	let fold = fold_json(&segments, &state.points.flatten(), &state.paper, &default_tolerance());
+	// the corners, the middle of each side and the center
+	assert_eq!(count(&fold, "vertices_coords"), 9);
+	assert_eq!(count(&fold, "vertices_axioms:count"), 9);
+	// each side is cut in two, and the creases are cut in two at the center
+	assert_eq!(count(&fold, "edges_vertices"), 16);
+	assert_eq!(count(&fold, "edges_assignment"), 16);
+	assert_eq!(fold.matches("\"B\"").count(), 8);
+	assert_eq!(fold.matches("\"U\"").count(), 8);
+	assert_eq!(count(&fold, "edges_axioms:axiom"), 16);
+	assert_eq!(count(&fold, "faces_vertices"), 8);
+	let faces = fold.lines().find(|row| row.starts_with("\"faces_vertices\"")).unwrap();
+	let faces = faces.trim_start_matches("\"faces_vertices\": [[").trim_end_matches("]]");
+	assert!(faces.split("],[").all(|face| face.split(',').count() == 3));
+}
+
+#[test]
+fn paper_alone () {
+	let state = make_state(make_unit_square(), Provenance::None);
+	let fold = fold_json(&vec![], &state.points.flatten(), &state.paper, &default_tolerance());
+	assert_eq!(count(&fold, "vertices_coords"), 4);
+	assert_eq!(count(&fold, "edges_vertices"), 4);
+	assert_eq!(count(&fold, "faces_vertices"), 1);
+}
+
+// the hexagon is drawn in the unit square, its vertices are in its own
+// units, radius 1 around 0,0
+#[test]
+fn paper_units () {
+	let state = make_state(make_regular_polygon(6).unwrap(), Provenance::None);
+	let fold = fold_json(&vec![], &state.points.flatten(), &state.paper, &default_tolerance());
+	assert!(fold.contains("\"frame_unit\": \"unit\""));
+	let row = fold.lines().find(|row| row.starts_with("\"vertices_coords\"")).unwrap();
+	let coords: Vec<f64> = row.trim_start_matches("\"vertices_coords\": [[").trim_end_matches("]],")
+		.split("],[").flat_map(|pair| pair.split(',')).map(|el| el.parse::<f64>().unwrap())
+		.collect();
+	assert_eq!(coords.len(), 12);
+	for i in 0..6 {
+		let radius = (coords[2 * i].powi(2) + coords[2 * i + 1].powi(2)).sqrt();
+		assert!((radius - 1.0).abs() < 1e-12, "vertex {} is {} from the center", i, radius);
+	}
+}
+
+// a segment which isn't a number is left out, instead of stopping the export
+#[test]
+fn not_a_number () {
+	let state = one_round();
+	let mut segments = segments(&state);
+	let fold = fold_json(&segments, &state.points.flatten(), &state.paper, &default_tolerance());
+	let nan = std::f64::NAN;
+	let broken = Segment { a: Vector { x: nan, y: 0.5 }, b: Vector { x: 1.0, y: nan } };
+	segments.insert(3, (broken, 1, 1, 1));
+	assert_eq!(fold_json(&segments, &state.points.flatten(), &state.paper, &default_tolerance()), fold);
+}
+
+// vertices closer than the tolerance are one, like points are. two creases
+// 2e-7 apart meet the sides and the midline 2e-7 apart
+#[test]
+fn vertices_within_the_tolerance () {
+	let state = make_state(make_unit_square(), Provenance::None);
+	let segment = |ax: f64, ay: f64, bx: f64, by: f64| (Segment { a: Vector { x: ax, y: ay }, b: Vector { x: bx, y: by } }, 1, 1, 1);
+	let segments = vec![
+		segment(0.0, 0.5, 1.0, 0.5),
+		segment(0.5, 0.0, 0.5, 1.0),
+		segment(0.5 + 2.0e-7, 0.0, 0.5 + 2.0e-7, 1.0),
+	];
+	let fold = fold_json(&segments, &vec![], &state.paper, &default_tolerance());
+	assert_eq!(count(&fold, "vertices_coords"), 4 + 2 + 2 + 2 + 2);
+	let fold = fold_json(&segments, &vec![], &state.paper, &make_tolerance(1.0e-6).unwrap());
+	assert_eq!(count(&fold, "vertices_coords"), 4 + 2 + 2 + 1);
+}
@@ -0,0 +1,150 @@
+extern crate origami_axioms;
+
+use origami_axioms::math::Line;
+use origami_axioms::origami;
+use origami_axioms::CountLine;
+use origami_axioms::Origin;
+use origami_axioms::Provenance;
+use origami_axioms::Tolerance;
+use origami_axioms::EdgeCount;
+use origami_axioms::tolerance::default_tolerance;
+use origami_axioms::index::refile_lines;
+use origami_axioms::LineContainer;
+use origami_axioms::make_round;
+use origami_axioms::make_round_plan;
+use origami_axioms::make_state;
+use origami_axioms::PointIndex;
+use origami_axioms::LineIndex;
+use origami_axioms::polygon::make_unit_square;
+
+// the simplest line index, one list searched from start to end
+struct LineList {
+	lines: Vec<CountLine>,
+	origins: Vec<Vec<Origin>>,
+	provenance: Provenance,
+	round: u8,
+	tolerance: Tolerance,
+	edges: EdgeCount,
+}
+
+fn make_line_list (provenance: Provenance) -> LineList {
+	return LineList {
+		lines: vec![],
+		origins: vec![],
+		provenance,
+		round: 0,
+		tolerance: default_tolerance(),
+		edges: EdgeCount::default(),
+	};
+}
+
+impl LineIndex for LineList {
+	fn make_empty (&self) -> LineList {
+		let mut list = make_line_list(self.provenance);
+		list.round = self.round;
+		return list;
+	}
+	fn provenance (&self) -> Provenance { self.provenance }
+	fn round (&self) -> u8 { self.round }
+	fn set_round (&mut self, round: u8) { self.round = round; }
+	fn tolerance (&self) -> Tolerance { self.tolerance }
+	fn set_tolerance (&mut self, tolerance: Tolerance) {
+		let lines = self.drain();
+		self.tolerance = tolerance;
+		refile_lines(self, lines);
+	}
+	fn edges (&self) -> EdgeCount { self.edges }
+	fn add_edges (&mut self, edges: &EdgeCount) { self.edges.add(edges); }
+	fn push_counted (&mut self, line: CountLine, origins: &Vec<Origin>) {
+		self.lines.push(line);
+		self.origins.push(self.provenance.keep(origins));
+	}
+	fn increment_nearest_by (&mut self, line: &Line, count: u64, origins: &Vec<Origin>) -> (bool, f64) {
+		let tolerance = self.tolerance;
+		let distances: Vec<f64> = self.lines.iter().map(|el| tolerance.line_distance(&el.0, line)).collect();
+		let found = distances.iter().position(|distance| tolerance.within(*distance));
+		let nearest = match found {
+			Some(i) => distances[i],
+			None => distances.iter().cloned().fold(std::f64::INFINITY, f64::min),
+		};
+		let i = match found {
+			Some(i) => i,
+			None => return (false, nearest),
+		};
+		self.lines[i].1 += count;
+		self.provenance.repeat(&mut self.origins[i], origins);
+		return (true, nearest);
+	}
+	fn find_origins (&self, line: &Line) -> Option<&Vec<Origin>> {
+		let tolerance = self.tolerance;
+		let i = self.lines.iter().position(|el| tolerance.within(tolerance.line_distance(&el.0, line)))?;
+		return Some(&self.origins[i]);
+	}
+	fn drain (&mut self) -> Vec<(CountLine, Vec<Origin>)> {
+		let lines = std::mem::replace(&mut self.lines, Vec::new());
+		let origins = std::mem::replace(&mut self.origins, Vec::new());
+		return lines.into_iter().zip(origins.into_iter()).collect();
+	}
+	fn merge (&mut self, other: &mut LineList) {
+		self.edges.add(&other.edges);
+		other.edges = EdgeCount::default();
+		self.lines.append(&mut other.lines);
+		self.origins.append(&mut other.origins);
+	}
+	fn flatten_filter (&self, count: u64) -> Vec<CountLine> {
+		return self.lines.iter().filter(|el| el.1 >= count).cloned().collect();
+	}
+	fn len (&self) -> usize { self.lines.len() }
+}
+
+// the same lines as a list, sorted, to compare indices which list them in
+// different orders
+fn sorted (lines: Vec<CountLine>) -> Vec<(i64, i64, i64, u64, u8, u8)> {
+	let key = |value: f64| (value * 1.0e6).round() as i64;
+	let mut list: Vec<(i64, i64, i64, u64, u8, u8)> = lines.iter().map(|el| {
+		// (u, d) and (-u, -d) are the same line
+		let sign = if el.0.d < -1.0e-9 || (el.0.d.abs() < 1.0e-9 && el.0.u.x < 0.0) { -1.0 } else { 1.0 };
+		(key(sign * el.0.u.x), key(sign * el.0.u.y), key(sign * el.0.d), el.1, el.2, el.3)
+	}).collect();
+	list.sort();
+	return list;
+}
+
+#[test]
+fn axioms_with_a_line_list () {
+	let state = make_state(make_unit_square(), Provenance::None);
+	let points = state.points.flatten();
+	let lines = state.lines.flatten();
+	let mut old_list = make_line_list(Provenance::None);
+	for line in lines.iter() { old_list.push_counted(*line, &vec![]); }
+	let mut new_list = old_list.make_empty();
+	let mut old_container: LineContainer = state.lines;
+	let mut new_container = old_container.make_empty();
+	origami::make_axiom1(&points, &mut old_list, &mut new_list, &state.paper, 0);
+	origami::make_axiom2(&points, &mut old_list, &mut new_list, &state.paper, 0);
+	origami::make_axiom4(&points, &lines, &mut old_list, &mut new_list, &state.paper, 0);
+	origami::make_axiom1(&points, &mut old_container, &mut new_container, &state.paper, 0);
+	origami::make_axiom2(&points, &mut old_container, &mut new_container, &state.paper, 0);
+	origami::make_axiom4(&points, &lines, &mut old_container, &mut new_container, &state.paper, 0);
+	assert_eq!(sorted(new_list.flatten()), sorted(new_container.flatten()));
+	assert_eq!(sorted(old_list.flatten()), sorted(old_container.flatten()));
+}
+
+#[test]
+fn rounds_with_a_line_list () {
+	let mut plan = make_round_plan();
+	plan.axioms = vec![1, 2];
+	plan.intersections = true;
+	let state = make_state(make_unit_square(), Provenance::First);
+	let mut list = make_line_list(Provenance::First);
+	for line in state.lines.flatten() { list.push_counted(line, &vec![]); }
+	let (mut points, mut lines) = (state.points, state.lines);
+	let mut list_points = points.make_empty();
+	for point in points.flatten() { list_points.push_counted(point, &vec![]); }
+	for round in 0..2 {
+		make_round(round, &plan, &mut points, &mut lines, &state.paper);
+		make_round(round, &plan, &mut list_points, &mut list, &state.paper);
+	}
+	assert_eq!(sorted(list.flatten()), sorted(lines.flatten()));
+	assert_eq!(list_points.len(), points.len());
+}
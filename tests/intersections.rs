@@ -0,0 +1,114 @@
+extern crate origami_axioms;
+
+use origami_axioms::math::Vector;
+use origami_axioms::math::Line;
+use origami_axioms::CountLine;
+use origami_axioms::GridVec;
+use origami_axioms::make_grid;
+use origami_axioms::make_intersections;
+use origami_axioms::polygon::make_unit_square;
+use origami_axioms::PointIndex;
+
+// the line through two points, as a line made in round 1 by axiom 1
+fn line (a: (f64, f64), b: (f64, f64)) -> CountLine {
+	let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+	let length = (dx * dx + dy * dy).sqrt();
+	let u = Vector { x: -dy / length, y: dx / length };
+	return (Line { u: u, d: u.x * a.0 + u.y * a.1 }, 1, 1, 1);
+}
+
+fn diagonals () -> Vec<CountLine> {
+	vec![line((0.0, 0.0), (1.0, 1.0)), line((1.0, 0.0), (0.0, 1.0))]
+}
+
+fn midlines () -> Vec<CountLine> {
+	vec![line((0.5, 0.0), (0.5, 1.0)), line((0.0, 0.5), (1.0, 0.5))]
+}
+
+fn count_at (points: &GridVec, x: f64, y: f64) -> u64 {
+	let target = Vector { x, y };
+	return points.flatten().iter().find(|el| el.0.equivalent(target)).map(|el| el.1).unwrap_or(0);
+}
+
+#[test]
+fn no_old_lines () {
+	let mut points: GridVec = make_grid();
+	let mut lines = diagonals();
+	lines.extend(midlines());
+	let new_points = make_intersections(&mut points, &vec![], &lines, &make_unit_square());
+	// 4 lines through the center, 6 pairs
+	assert_eq!(new_points.len(), 1);
+	assert_eq!(count_at(&new_points, 0.5, 0.5), 6);
+}
+
+#[test]
+fn no_new_lines () {
+	let mut points: GridVec = make_grid();
+	let new_points = make_intersections(&mut points, &diagonals(), &vec![], &make_unit_square());
+	assert_eq!(new_points.len(), 0);
+}
+
+#[test]
+fn no_lines () {
+	let mut points: GridVec = make_grid();
+	let new_points = make_intersections(&mut points, &vec![], &vec![], &make_unit_square());
+	assert_eq!(new_points.len(), 0);
+}
+
+// old lines aren't crossed with each other again, only with the new lines
+#[test]
+fn new_and_old_lines () {
+	let mut points: GridVec = make_grid();
+	let old = diagonals();
+	let new = vec![line((0.0, 0.25), (1.0, 0.25))];
+	let new_points = make_intersections(&mut points, &old, &new, &make_unit_square());
+	assert_eq!(new_points.len(), 2);
+	assert_eq!(count_at(&new_points, 0.25, 0.25), 1);
+	assert_eq!(count_at(&new_points, 0.75, 0.25), 1);
+	assert_eq!(count_at(&new_points, 0.5, 0.5), 0);
+}
+
+// points which already exist are counted again, and not made twice
+#[test]
+fn existing_points () {
+	let mut points: GridVec = make_grid();
+	points.push(Vector { x: 0.5, y: 0.5 });
+	let new_points = make_intersections(&mut points, &diagonals(), &midlines(), &make_unit_square());
+	assert_eq!(new_points.len(), 0);
+	// 2 midlines cross each other, and each crosses 2 diagonals
+	assert_eq!(count_at(&points, 0.5, 0.5), 1 + 1 + 4);
+}
+
+fn total (points: &GridVec) -> u64 {
+	return points.flatten().iter().map(|el| el.1).sum();
+}
+
+// splitting the lines into old and new at any place, the new lines cross
+// every pair which the old lines didn't already cross among themselves
+#[test]
+fn every_split_of_old_and_new () {
+	let mut all = diagonals();
+	all.extend(midlines());
+	all.push(line((0.0, 0.25), (1.0, 0.25)));
+	all.push(line((0.25, 0.0), (0.25, 1.0)));
+	let paper = make_unit_square();
+	let everything = total(&make_intersections(&mut make_grid(), &vec![], &all, &paper));
+	for split in 0..all.len() + 1 {
+		let old = all[..split].to_vec();
+		let mut new = all[split..].to_vec();
+		new.reverse();
+		let before = total(&make_intersections(&mut make_grid(), &vec![], &old, &paper));
+		let after = total(&make_intersections(&mut make_grid(), &old, &new, &paper));
+		assert_eq!(before + after, everything);
+	}
+}
+
+// lines crossing outside the paper make no point
+#[test]
+fn outside_the_paper () {
+	let mut points: GridVec = make_grid();
+	let inside = vec![line((0.0, 0.5), (1.0, 0.6)), line((0.0, 0.6), (1.0, 0.5))];
+	assert_eq!(make_intersections(&mut points, &vec![], &inside, &make_unit_square()).len(), 1);
+	let outside = vec![line((0.0, 0.5), (1.0, 0.6)), line((0.0, 0.7), (1.0, 0.7))];
+	assert_eq!(make_intersections(&mut points, &vec![], &outside, &make_unit_square()).len(), 0);
+}
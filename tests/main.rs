@@ -1,58 +1,279 @@
 extern crate origami_axioms;
 
+use std::env;
+use std::fs;
+use std::path::Path;
 use origami_axioms::math::Vector;
-use origami_axioms::math::Rect;
-use origami_axioms::math::make_square;
+use origami_axioms::math::Line;
 use origami_axioms::origami;
-// use origami_axioms::GridVec;
-use origami_axioms::QuadTree;
-// use origami_axioms::make_grid;
-use origami_axioms::make_tree;
+use origami_axioms::GridVec;
 use origami_axioms::LineContainer;
 use origami_axioms::make_line_container;
+use origami_axioms::make_round;
+use origami_axioms::make_round_plan;
+use origami_axioms::make_state;
+use origami_axioms::Provenance;
+use origami_axioms::State;
+use origami_axioms::polygon::make_unit_square;
+use origami_axioms::PointIndex;
+use origami_axioms::LineIndex;
 
-// use make_line_container;
-// use make_grid;
-// use fold;
-// use GridVec;
-// use LineContainer;
+// the unit square's corners and sides
+fn unit_square () -> State {
+	return make_state(make_unit_square(), Provenance::None);
+}
 
-// const EPSILON: f64 = f64::EPSILON * 10.0;
+// "rounds" rounds of these axioms, with intersections, from the unit square
+fn explore (axioms: &[u8], rounds: usize) -> State {
+	let mut state = unit_square();
+	for round in 0..rounds {
+		let mut plan = make_round_plan();
+		plan.axioms = axioms.to_vec();
+		plan.intersections = true;
+		make_round(round, &plan, &mut state.points, &mut state.lines, &state.paper);
+		state.rounds += 1;
+	}
+	return state;
+}
 
-// macro_rules! assert_delta {
-// 	($x:expr, $y:expr, $d:expr) => {
-// 		assert_eq!(true, $x - $y < $d && $y - $x < $d);
-// 	}
-// }
+fn point_count (points: &GridVec, x: f64, y: f64) -> u64 {
+	let target = Vector { x, y };
+	return points.flatten().iter().find(|el| el.0.equivalent(target)).map(|el| el.1).unwrap_or(0);
+}
+
+// the count of the line through two points
+fn line_count (lines: &LineContainer, a: (f64, f64), b: (f64, f64)) -> u64 {
+	let (a, b) = (Vector { x: a.0, y: a.1 }, Vector { x: b.0, y: b.1 });
+	let on = |line: &Line, p: Vector| (line.u.x * p.x + line.u.y * p.y - line.d).abs() < 1.0e-9;
+	return lines.flatten().iter().find(|el| on(&el.0, a) && on(&el.0, b)).map(|el| el.1).unwrap_or(0);
+}
 
 #[test]
 fn make_axiom_tests () {
-	let boundary: Rect = make_square();
-	// let mut point_quadtree: GridVec = make_grid();
-	let mut point_quadtree: QuadTree = make_tree();
-	let mut line_container: LineContainer = make_line_container();
-	point_quadtree.push(Vector { x: 0.0, y: 0.0 });
-	point_quadtree.push(Vector { x: 1.0, y: 0.0 });
-	point_quadtree.push(Vector { x: 1.0, y: 1.0 });
-	point_quadtree.push(Vector { x: 0.0, y: 1.0 });
-	boundary.sides.iter().for_each(|side| line_container.push(side));
-	let points = point_quadtree.flatten();
-	let lines = line_container.flatten();
-	// let _points1 = point_quadtree.flatten_filter(0);
-	// let _points2 = point_quadtree.filter_by_count(0);
-	// let _lines1 = line_container.flatten_filter(0);
+	let state = unit_square();
+	let points = state.points.flatten();
+	let lines = state.lines.flatten();
+	let mut line_container: LineContainer = state.lines;
 	let mut new_line_container: LineContainer = make_line_container();
-	origami::make_axiom1(&points, &mut line_container, &mut new_line_container);
-	origami::make_axiom2(&points, &mut line_container, &mut new_line_container);
-	origami::make_axiom3(&points, &lines, &mut line_container, &mut new_line_container, boundary);
-	origami::make_axiom4(&points, &lines, &mut line_container, &mut new_line_container, boundary);
-	origami::make_axiom5(&points, &lines, &mut line_container, &mut new_line_container, boundary);
-	origami::make_axiom6(&points, &lines, &mut line_container, &mut new_line_container, boundary);
-	origami::make_axiom7(&points, &lines, &mut line_container, &mut new_line_container, boundary);
+	origami::make_axiom1(&points, &mut line_container, &mut new_line_container, &state.paper, 0);
+	origami::make_axiom2(&points, &mut line_container, &mut new_line_container, &state.paper, 0);
+	origami::make_axiom3(&points, &lines, &mut line_container, &mut new_line_container, &state.paper, 0);
+	origami::make_axiom4(&points, &lines, &mut line_container, &mut new_line_container, &state.paper, 0);
+	origami::make_axiom5(&points, &lines, &mut line_container, &mut new_line_container, &state.paper, 0);
+	origami::make_axiom6(&points, &lines, &mut line_container, &mut new_line_container, &state.paper, 0);
+	origami::make_axiom7(&points, &lines, &mut line_container, &mut new_line_container, &state.paper, 0);
+	// axioms 1 and 2 alone make the two diagonals and the two midlines
+	assert!(new_line_container.len() >= 4);
+	assert_eq!(line_container.len(), 4);
 	let new_lines = new_line_container.flatten();
 	let old_lines = line_container.flatten();
-	// let _new_points: GridVec = origami::make_intersections(
-	// 	&mut point_quadtree, &old_lines, &new_lines, boundary);
-	let _new_points: QuadTree = origami::make_intersections(
-		&mut point_quadtree, &old_lines, &new_lines, boundary);
+	let mut point_grid = state.points;
+	let new_points: GridVec = origami::make_intersections(
+		&mut point_grid, &old_lines, &new_lines, &state.paper);
+	// at least the center, and the middle of each side
+	assert!(new_points.len() >= 5);
+	assert!(point_count(&new_points, 0.5, 0.5) > 0);
+}
+
+// 6 pairs of corners: 4 pairs make the sides again, 2 make the diagonals.
+// each diagonal crosses 2 sides at each of its corners
+#[test]
+fn round_one_axiom_1 () {
+	let state = explore(&[1], 1);
+	assert_eq!(state.lines.len(), 6);
+	assert_eq!(line_count(&state.lines, (0.0, 0.0), (1.0, 0.0)), 2);
+	assert_eq!(line_count(&state.lines, (0.0, 0.0), (1.0, 1.0)), 1);
+	assert_eq!(line_count(&state.lines, (1.0, 0.0), (0.0, 1.0)), 1);
+	assert_eq!(state.points.len(), 5);
+	assert_eq!(point_count(&state.points, 0.5, 0.5), 1);
+	assert_eq!(point_count(&state.points, 0.0, 0.0), 3);
+	assert_eq!(point_count(&state.points, 1.0, 1.0), 3);
+}
+
+// 4 pairs of neighboring corners make the 2 midlines twice each,
+// 2 pairs of opposite corners make the diagonals. all 4 cross at the center
+#[test]
+fn round_one_axiom_2 () {
+	let state = explore(&[2], 1);
+	assert_eq!(state.lines.len(), 8);
+	assert_eq!(line_count(&state.lines, (0.5, 0.0), (0.5, 1.0)), 2);
+	assert_eq!(line_count(&state.lines, (0.0, 0.5), (1.0, 0.5)), 2);
+	assert_eq!(line_count(&state.lines, (0.0, 0.0), (1.0, 1.0)), 1);
+	assert_eq!(line_count(&state.lines, (0.0, 0.0), (1.0, 0.0)), 1);
+	assert_eq!(state.points.len(), 9);
+	assert_eq!(point_count(&state.points, 0.5, 0.5), 6);
+	assert_eq!(point_count(&state.points, 0.5, 0.0), 1);
+	assert_eq!(point_count(&state.points, 0.0, 0.5), 1);
+	assert_eq!(point_count(&state.points, 0.0, 1.0), 3);
+}
+
+// the perpendicular to a side through a corner is always a side
+#[test]
+fn round_one_axiom_4 () {
+	let state = explore(&[4], 1);
+	assert_eq!(state.lines.len(), 4);
+	assert_eq!(line_count(&state.lines, (0.0, 0.0), (0.0, 1.0)), 5);
+	assert_eq!(state.points.len(), 4);
+}
+
+// each pair of sides has two bisectors. the parallel pairs make the midlines,
+// the 4 pairs meeting at a corner make a diagonal (each diagonal twice), and
+// their other bisector only touches the corner, it doesn't cross the paper
+#[test]
+fn round_one_axiom_3 () {
+	let state = explore(&[3], 1);
+	assert_eq!(state.lines.len(), 8);
+	assert_eq!(line_count(&state.lines, (0.5, 0.0), (0.5, 1.0)), 1);
+	assert_eq!(line_count(&state.lines, (0.0, 0.5), (1.0, 0.5)), 1);
+	assert_eq!(line_count(&state.lines, (0.0, 0.0), (1.0, 1.0)), 2);
+	assert_eq!(line_count(&state.lines, (1.0, 0.0), (0.0, 1.0)), 2);
+	assert_eq!(line_count(&state.lines, (0.0, 0.0), (1.0, 0.0)), 1);
+	assert_eq!(state.points.len(), 9);
+	assert_eq!(point_count(&state.points, 0.5, 0.5), 6);
+	assert_eq!(point_count(&state.points, 1.0, 0.5), 1);
+	assert_eq!(point_count(&state.points, 0.0, 0.0), 3);
+}
+
+// the fold through a corner which brings a neighboring corner onto a side
+// is the diagonal through it: (1, 0) lands on (0, 1) folding along y = x
+// through (0, 0). each corner pivots both its neighbors onto 2 sides each.
+// the opposite corner is too far to reach any side but at itself
+#[test]
+fn round_one_axiom_5 () {
+	let state = explore(&[5], 1);
+	assert_eq!(state.lines.len(), 6);
+	assert_eq!(line_count(&state.lines, (0.0, 0.0), (1.0, 1.0)), 8);
+	assert_eq!(line_count(&state.lines, (1.0, 0.0), (0.0, 1.0)), 8);
+	assert_eq!(line_count(&state.lines, (0.0, 0.0), (0.0, 1.0)), 1);
+	assert_eq!(state.points.len(), 5);
+	assert_eq!(point_count(&state.points, 0.5, 0.5), 1);
+	assert_eq!(point_count(&state.points, 1.0, 0.0), 3);
+}
+
+// folding two corners onto two sides at once only finds the square's
+// symmetries. 16 of the 144 choices of corners and sides are a whole family
+// of folds (any horizontal fold brings (0, 0) onto the left side and (1, 0)
+// onto the right) and make nothing. of the rest, 32 make each midline and
+// 20 make each diagonal
+#[test]
+fn round_one_axiom_6 () {
+	let state = explore(&[6], 1);
+	assert_eq!(state.lines.len(), 8);
+	assert_eq!(line_count(&state.lines, (0.5, 0.0), (0.5, 1.0)), 32);
+	assert_eq!(line_count(&state.lines, (0.0, 0.5), (1.0, 0.5)), 32);
+	assert_eq!(line_count(&state.lines, (0.0, 0.0), (1.0, 1.0)), 20);
+	assert_eq!(line_count(&state.lines, (1.0, 0.0), (0.0, 1.0)), 20);
+	assert_eq!(state.points.len(), 9);
+	assert_eq!(point_count(&state.points, 0.5, 0.5), 6);
+	assert_eq!(point_count(&state.points, 0.5, 1.0), 1);
+	assert_eq!(point_count(&state.points, 1.0, 1.0), 3);
+}
+
+// a fold perpendicular to one side is parallel to the sides next to it, so
+// it can only bring a corner onto the parallel side across from it, along a
+// midline. each corner does that for 2 sides, perpendicular to 2 sides each
+#[test]
+fn round_one_axiom_7 () {
+	let state = explore(&[7], 1);
+	assert_eq!(state.lines.len(), 6);
+	assert_eq!(line_count(&state.lines, (0.5, 0.0), (0.5, 1.0)), 8);
+	assert_eq!(line_count(&state.lines, (0.0, 0.5), (1.0, 0.5)), 8);
+	assert_eq!(state.points.len(), 9);
+	assert_eq!(point_count(&state.points, 0.5, 0.5), 1);
+	assert_eq!(point_count(&state.points, 0.0, 0.5), 1);
+	// no new line goes through a corner
+	assert_eq!(point_count(&state.points, 0.0, 0.0), 1);
+}
+
+#[test]
+fn round_one_axioms_1_2 () {
+	let state = explore(&[1, 2], 1);
+	assert_eq!(state.lines.len(), 8);
+	assert_eq!(line_count(&state.lines, (0.0, 0.0), (1.0, 0.0)), 2);
+	assert_eq!(line_count(&state.lines, (0.0, 0.0), (1.0, 1.0)), 2);
+	assert_eq!(line_count(&state.lines, (0.5, 0.0), (0.5, 1.0)), 2);
+	assert_eq!(state.points.len(), 9);
+	assert_eq!(point_count(&state.points, 0.5, 0.5), 6);
+	assert_eq!(point_count(&state.points, 1.0, 0.0), 3);
+}
+
+fn tally (groups: &mut Vec<(String, usize)>, key: String) {
+	match groups.iter().position(|el| el.0 == key) {
+		Some(i) => groups[i].1 += 1,
+		None => groups.push((key, 1)),
+	}
+}
+
+// a summary of an exploration: totals, lines by axiom, lines and points by
+// count, and everything by round
+fn summary (state: &State) -> String {
+	let lines = state.lines.flatten();
+	let points = state.points.flatten();
+	let mut text = format!("lines {}\npoints {}\n", lines.len(), points.len());
+	let mut groups: Vec<(String, usize)> = Vec::new();
+	for line in lines.iter() {
+		tally(&mut groups, format!("lines axiom {}", line.2));
+		tally(&mut groups, format!("lines round {}", line.3));
+		tally(&mut groups, format!("lines count {}", line.1));
+	}
+	for point in points.iter() {
+		tally(&mut groups, format!("points round {}", point.2));
+		tally(&mut groups, format!("points count {}", point.1));
+	}
+	groups.sort_by(|a, b| a.0.cmp(&b.0));
+	for group in groups.iter() {
+		text.push_str(&format!("{}: {}\n", group.0, group.1));
+	}
+	return text;
+}
+
+// round 2 folds the 3 x 3 grid of round 1's points. axiom 1 makes 20 lines
+// through them: 8 through three points (which round 1 already made) and 12
+// through two. axiom 2 makes 12 new perpendicular bisectors. every point
+// where any two of the 32 lines cross is made
+#[test]
+fn round_two_axioms_1_2 () {
+	let state = explore(&[1, 2], 2);
+	assert_eq!(state.lines.len(), 32);
+	assert_eq!(state.points.len(), 133);
+}
+
+// compare against tests/golden/NAME. to record new values after a change
+// which is meant to change the results, run with GOLDEN_UPDATE=1.
+// the files were recorded that way (GOLDEN_UPDATE=1 cargo test --test main
+// golden). the totals of axioms 1 and 2 are the ones asserted by
+// round_one_axioms_1_2 and round_two_axioms_1_2, the default plan's are the
+// command line's (axiom-drawings -r 1, and -r 2), the rest is what the build
+// recording them made
+fn golden (name: &str, actual: &str) {
+	let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(name);
+	if env::var("GOLDEN_UPDATE").is_ok() {
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		fs::write(&path, actual).unwrap();
+		return;
+	}
+	let expected = fs::read_to_string(&path)
+		.expect("missing golden file, record it with GOLDEN_UPDATE=1");
+	assert_eq!(expected, actual, "{} changed", name);
+}
+
+#[test]
+fn golden_round_1 () {
+	golden("axioms-1-2-round-1.txt", &summary(&explore(&[1, 2], 1)));
+}
+
+#[test]
+fn golden_round_2 () {
+	golden("axioms-1-2-round-2.txt", &summary(&explore(&[1, 2], 2)));
+}
+
+// the default plan: every axiom, with intersections
+#[test]
+fn golden_all_axioms_round_1 () {
+	golden("axioms-1-7-round-1.txt", &summary(&explore(&[1, 2, 3, 4, 5, 6, 7], 1)));
+}
+
+#[test]
+fn golden_all_axioms_round_2 () {
+	golden("axioms-1-7-round-2.txt", &summary(&explore(&[1, 2, 3, 4, 5, 6, 7], 2)));
 }
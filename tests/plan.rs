@@ -0,0 +1,110 @@
+extern crate origami_axioms;
+
+use origami_axioms::math::Vector;
+use origami_axioms::math::Line;
+use origami_axioms::GridVec;
+use origami_axioms::make_grid;
+use origami_axioms::make_tracked_grid;
+use origami_axioms::LineContainer;
+use origami_axioms::make_line_container;
+use origami_axioms::make_tracked_line_container;
+use origami_axioms::make_round_plan;
+use origami_axioms::plan::apply_preset;
+use origami_axioms::Origin;
+use origami_axioms::Provenance;
+use origami_axioms::PointIndex;
+use origami_axioms::LineIndex;
+
+fn vertical (x: f64) -> Line {
+	return Line { u: Vector { x: 1.0, y: 0.0 }, d: x };
+}
+
+// three points, counted 1, 2 and 3 times
+fn counted_grid (provenance: Provenance) -> GridVec {
+	let mut grid: GridVec = make_tracked_grid(provenance);
+	for i in 0..3 {
+		let point = Vector { x: 0.25 * (i + 1) as f64, y: 0.5 };
+		let origin = Origin { axiom: 0, points: vec![], lines: vec![vertical(point.x), vertical(0.0)] };
+		grid.push_counted((point, i + 1, 1), &vec![origin]);
+	}
+	return grid;
+}
+
+// three lines, counted 1, 2 and 3 times
+fn counted_lines (provenance: Provenance) -> LineContainer {
+	let mut lines: LineContainer = make_tracked_line_container(provenance);
+	for i in 0..3 {
+		let line = vertical(0.25 * (i + 1) as f64);
+		let origin = Origin { axiom: 1, points: vec![Vector { x: line.d, y: 0.0 }], lines: vec![] };
+		lines.push_counted((line, i + 1, 1, 1), &vec![origin]);
+	}
+	return lines;
+}
+
+#[test]
+fn filters_by_axiom () {
+	let mut plan = make_round_plan();
+	plan.set_filter(5, 4, 3);
+	assert_eq!((plan.min_points(5), plan.min_lines(5)), (4, 3));
+	assert_eq!((plan.min_points(1), plan.min_lines(1)), (0, 0));
+	plan.set_filter(0, 2, 1);
+	for axiom in 1..=7 {
+		assert_eq!((plan.min_points(axiom), plan.min_lines(axiom)), (2, 1));
+	}
+	// axioms which don't exist have no filter
+	assert_eq!((plan.min_points(0), plan.min_lines(0)), (0, 0));
+	assert_eq!((plan.min_points(8), plan.min_lines(8)), (0, 0));
+}
+
+#[test]
+fn presets () {
+	let mut plan = make_round_plan();
+	assert!(apply_preset(&mut plan, "light", 2));
+	assert_eq!((plan.min_points(1), plan.min_lines(1)), (2, 0));
+	assert_eq!((plan.min_points(4), plan.min_lines(4)), (2, 0));
+	assert_eq!((plan.min_points(5), plan.min_lines(5)), (8, 8));
+	assert_eq!((plan.min_points(7), plan.min_lines(7)), (4, 4));
+	assert!(!apply_preset(&mut plan, "heavy", 2));
+}
+
+#[test]
+fn flatten_filter_keeps_repeated () {
+	let grid = counted_grid(Provenance::None);
+	assert_eq!(grid.flatten_filter(0).len(), 3);
+	assert_eq!(grid.flatten_filter(1).len(), 3);
+	let twice: Vec<u64> = grid.flatten_filter(2).iter().map(|el| el.1).collect();
+	assert_eq!(twice, vec![2, 3]);
+	assert_eq!(grid.flatten_filter(4).len(), 0);
+	let lines = counted_lines(Provenance::None);
+	assert_eq!(lines.flatten_filter(0).len(), 3);
+	let thrice: Vec<u64> = lines.flatten_filter(3).iter().map(|el| el.1).collect();
+	assert_eq!(thrice, vec![3]);
+	assert_eq!(lines.flatten_filter(4).len(), 0);
+	assert_eq!(make_grid().flatten_filter(2).len(), 0);
+	assert_eq!(make_line_container().flatten_filter(2).len(), 0);
+}
+
+// the copies keep the counts, rounds, origins, and can be searched
+#[test]
+fn filter_by_count_copies () {
+	let grid = counted_grid(Provenance::All);
+	let filtered = grid.filter_by_count(2);
+	assert_eq!(filtered.len(), 2);
+	assert_eq!(filtered.provenance, Provenance::All);
+	let counts: Vec<u64> = filtered.flatten().iter().map(|el| el.1).collect();
+	assert_eq!(counts, vec![2, 3]);
+	let origins = filtered.find_origins(&Vector { x: 0.5, y: 0.5 }).unwrap();
+	assert_eq!(origins.len(), 1);
+	assert!(origins[0].lines[0].equivalent(vertical(0.5)));
+	assert!(filtered.find_origins(&Vector { x: 0.25, y: 0.5 }).is_none());
+
+	let lines = counted_lines(Provenance::First);
+	let filtered = lines.filter_by_count(3);
+	assert_eq!(filtered.len(), 1);
+	assert_eq!(filtered.flatten()[0].1, 3);
+	let origins = filtered.find_origins(&vertical(0.75)).unwrap();
+	assert!(origins[0].points[0].equivalent(Vector { x: 0.75, y: 0.0 }));
+	assert!(filtered.find_origins(&vertical(0.5)).is_none());
+	assert_eq!(lines.filter_by_count(0).len(), 3);
+	assert_eq!(counted_lines(Provenance::None).filter_by_count(2).len(), 2);
+}
@@ -0,0 +1,126 @@
+extern crate origami_axioms;
+
+use origami_axioms::math::Vector;
+use origami_axioms::math::Line;
+use origami_axioms::make_round;
+use origami_axioms::make_round_plan;
+use origami_axioms::make_state;
+use origami_axioms::Provenance;
+use origami_axioms::polygon::make_regular_polygon;
+use origami_axioms::LineIndex;
+
+fn point (x: f64, y: f64) -> Vector { Vector { x, y } }
+
+fn horizontal (y: f64) -> Line { Line { u: point(0.0, 1.0), d: y } }
+
+fn vertical (x: f64) -> Line { Line { u: point(1.0, 0.0), d: x } }
+
+fn reflect (line: &Line, p: Vector) -> Vector {
+	let distance = line.u.x * p.x + line.u.y * p.y - line.d;
+	return point(p.x - 2.0 * distance * line.u.x, p.y - 2.0 * distance * line.u.y);
+}
+
+// fitted into the unit square: (0, 0), (1, 0), (0.5, 0.866)
+#[test]
+fn triangle_contains () {
+	let triangle = make_regular_polygon(3).unwrap();
+	assert_eq!(triangle.vertices.len(), 3);
+	assert!(triangle.contains(point(0.5, 0.3)));
+	assert!(triangle.contains(point(0.5, 0.0)));
+	assert!(triangle.contains(point(1.0, 0.0)));
+	assert!(!triangle.contains(point(0.05, 0.8)));
+	assert!(!triangle.contains(point(0.0, 1.0)));
+	assert!(!triangle.contains(point(0.5, 0.9)));
+}
+
+#[test]
+fn triangle_keep () {
+	let triangle = make_regular_polygon(3).unwrap();
+	// above the top corner, still inside the frame
+	let kept = triangle.keep(vec![horizontal(0.95), horizontal(0.5), vertical(0.05)]);
+	assert_eq!(kept.len(), 2);
+	assert!(kept[0].equivalent(horizontal(0.5)));
+	assert!(kept[1].equivalent(vertical(0.05)));
+}
+
+// the fold crosses the paper, but the point lands off it, inside the frame
+#[test]
+fn triangle_keep_folds () {
+	let triangle = make_regular_polygon(3).unwrap();
+	let fold = vertical(0.3);
+	let moving = point(0.5, 0.5);
+	assert!(triangle.contains(moving));
+	assert!(triangle.frame.contains(reflect(&fold, moving)));
+	assert!(!triangle.contains(reflect(&fold, moving)));
+	assert_eq!(triangle.keep(vec![fold]).len(), 1);
+	assert_eq!(triangle.keep_folds(vec![fold], &[moving]).len(), 0);
+	assert_eq!(triangle.keep_folds(vec![vertical(0.5)], &[moving, point(0.4, 0.1)]).len(), 1);
+}
+
+// fitted into the unit square: (0.25, 0), (0.75, 0), (1, 0.433), (0.75, 0.866),
+// (0.25, 0.866), (0, 0.433)
+#[test]
+fn hexagon_contains () {
+	let hexagon = make_regular_polygon(6).unwrap();
+	assert_eq!(hexagon.vertices.len(), 6);
+	assert!(hexagon.contains(point(0.5, 0.433)));
+	assert!(hexagon.contains(point(0.25, 0.0)));
+	assert!(hexagon.contains(point(0.1, 0.433)));
+	assert!(!hexagon.contains(point(0.05, 0.05)));
+	assert!(!hexagon.contains(point(0.95, 0.85)));
+	assert!(!hexagon.contains(point(0.5, 0.9)));
+}
+
+#[test]
+fn hexagon_keep () {
+	let hexagon = make_regular_polygon(6).unwrap();
+	let corner = Line { u: point(0.5_f64.sqrt(), 0.5_f64.sqrt()), d: 0.1 };
+	let kept = hexagon.keep(vec![corner, horizontal(0.95), vertical(0.5), horizontal(0.2)]);
+	assert_eq!(kept.len(), 2);
+	assert!(kept[0].equivalent(vertical(0.5)));
+	assert!(kept[1].equivalent(horizontal(0.2)));
+	// folding the bottom side's middle up by 0.9 lands above the top side
+	assert_eq!(hexagon.keep_folds(vec![horizontal(0.45)], &[point(0.5, 0.0)]).len(), 0);
+	assert_eq!(hexagon.keep_folds(vec![horizontal(0.433)], &[point(0.5, 0.0)]).len(), 1);
+}
+
+// the hexagon has radius 1 around the origin in its own units
+#[test]
+fn hexagon_units () {
+	let hexagon = make_regular_polygon(6).unwrap();
+	assert!((hexagon.scale - 2.0).abs() < 1e-12);
+	assert!(hexagon.to_paper(point(0.5, 3_f64.sqrt() / 4.0)).equivalent(point(0.0, 0.0)));
+	assert!(hexagon.to_paper(hexagon.vertices[0]).equivalent(point(-0.5, -(3_f64.sqrt()) / 2.0)));
+	assert!(hexagon.from_paper(point(1.0, 0.0)).equivalent(point(1.0, 3_f64.sqrt() / 4.0)));
+	let p = point(0.3, 0.7);
+	assert!(hexagon.from_paper(hexagon.to_paper(p)).equivalent(p));
+	// the middle line is y = 0 in the hexagon's units
+	assert!(hexagon.line_to_paper(horizontal(3_f64.sqrt() / 4.0)).equivalent(horizontal(0.0)));
+	// a line's points stay on it in either units
+	let line = Line { u: point(0.6, 0.8), d: 0.5 };
+	let on = point(0.5, 0.25);
+	let moved = hexagon.line_to_paper(line);
+	let q = hexagon.to_paper(on);
+	assert!((moved.u.x * q.x + moved.u.y * q.y - moved.d).abs() < 1e-12);
+}
+
+// every point folded by axioms 5, 6 and 7 lands on the paper
+#[test]
+fn folded_points_stay_on_the_paper () {
+	for sides in [3, 6].iter() {
+		let mut state = make_state(make_regular_polygon(*sides).unwrap(), Provenance::All);
+		let mut plan = make_round_plan();
+		plan.axioms = vec![1, 5, 6, 7];
+		make_round(0, &plan, &mut state.points, &mut state.lines, &state.paper);
+		let lines = state.lines.flatten();
+		assert!(lines.iter().any(|el| el.2 >= 5));
+		for line in lines.iter().filter(|el| el.2 >= 5) {
+			for origin in state.lines.find_origins(&line.0).unwrap().iter() {
+				for p in origin.points.iter() {
+					assert!(state.paper.contains(reflect(&line.0, *p)),
+						"axiom {} folds a point off the paper", origin.axiom);
+				}
+			}
+		}
+	}
+}
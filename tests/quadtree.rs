@@ -0,0 +1,98 @@
+extern crate origami_axioms;
+
+use origami_axioms::math::Vector;
+use origami_axioms::GridVec;
+use origami_axioms::QuadTree;
+use origami_axioms::PointIndex;
+use origami_axioms::make_grid;
+use origami_axioms::make_tree;
+use origami_axioms::make_tracked_tree;
+use origami_axioms::make_round;
+use origami_axioms::make_round_plan;
+use origami_axioms::make_state;
+use origami_axioms::Provenance;
+use origami_axioms::index::copy_points;
+use origami_axioms::polygon::make_unit_square;
+use origami_axioms::LineIndex;
+
+// a 50 x 50 lattice, enough points to split the tree a few times
+fn lattice () -> Vec<Vector> {
+	let mut points: Vec<Vector> = Vec::new();
+	for i in 0..50 {
+		for j in 0..50 {
+			points.push(Vector { x: (i as f64 + 0.5) / 50.0, y: (j as f64 + 0.3) / 50.0 });
+		}
+	}
+	return points;
+}
+
+#[test]
+fn split_and_find () {
+	let mut tree: QuadTree = make_tree();
+	let points = lattice();
+	for i in 0..points.len() { tree.push(points[i]); }
+	assert!(tree.quadrants.len() == 4);
+	assert_eq!(tree.len(), points.len());
+	// every point is found in its own quadrant, none are made twice
+	for i in 0..points.len() { assert!(tree.increment_match(&points[i])); }
+	assert_eq!(tree.flatten().iter().filter(|el| el.1 == 2).count(), points.len());
+	assert!(!tree.increment_match(&Vector { x: 0.33, y: 0.77 }));
+}
+
+// a point a hair's width across an axis from another is the same point
+#[test]
+fn find_across_an_axis () {
+	let mut tree: QuadTree = make_tree();
+	let points = lattice();
+	for i in 0..points.len() { tree.push(points[i]); }
+	let below = Vector { x: 0.25, y: 0.5 - 1.0e-12 };
+	let above = Vector { x: 0.25, y: 0.5 + 1.0e-12 };
+	tree.push(below);
+	assert!(tree.increment_match(&above));
+	assert_eq!(tree.len(), points.len() + 1);
+	let center = Vector { x: 0.5 - 1.0e-12, y: 0.5 - 1.0e-12 };
+	tree.push(center);
+	assert!(tree.increment_match(&Vector { x: 0.5, y: 0.5 }));
+}
+
+#[test]
+fn merge_keeps_origins () {
+	let mut tree: QuadTree = make_tracked_tree(Provenance::First);
+	let mut other: QuadTree = make_tracked_tree(Provenance::First);
+	let points = lattice();
+	for i in 0..points.len() { other.push(points[i]); }
+	tree.merge(&mut other);
+	assert_eq!(other.len(), 0);
+	assert_eq!(tree.len(), points.len());
+	for i in 0..points.len() {
+		let origins = tree.find_origins(&points[i]).unwrap();
+		assert!(origins.len() == 1 && origins[0].initial());
+	}
+}
+
+// two rounds of axioms 1 and 2 make the same points and counts either way
+#[test]
+fn same_as_the_grid () {
+	let paper = make_unit_square();
+	let state = make_state(make_unit_square(), Provenance::None);
+	let mut grid: GridVec = state.points;
+	let mut tree: QuadTree = make_tree();
+	copy_points(&grid, &mut tree);
+	let mut grid_lines = state.lines;
+	let mut tree_lines = make_state(make_unit_square(), Provenance::None).lines;
+	let mut plan = make_round_plan();
+	plan.axioms = vec![1, 2];
+	plan.intersections = true;
+	for round in 0..2 {
+		make_round(round, &plan, &mut grid, &mut grid_lines, &paper);
+		make_round(round, &plan, &mut tree, &mut tree_lines, &paper);
+	}
+	assert_eq!(grid.len(), tree.len());
+	assert_eq!(grid_lines.len(), tree_lines.len());
+	let mut copy: GridVec = make_grid();
+	copy_points(&tree, &mut copy);
+	for point in grid.flatten() {
+		let found = copy.flatten().into_iter().find(|el| el.0.equivalent(point.0)).unwrap();
+		assert_eq!((found.1, found.2), (point.1, point.2));
+	}
+}
@@ -0,0 +1,195 @@
+extern crate origami_axioms;
+
+use origami_axioms::math::Vector;
+use origami_axioms::math::Line;
+use origami_axioms::origami::sequence;
+use origami_axioms::origami::sequence::Step;
+use origami_axioms::GridVec;
+use origami_axioms::make_tracked_grid;
+use origami_axioms::QuadTree;
+use origami_axioms::make_tracked_tree;
+use origami_axioms::index::copy_points;
+use origami_axioms::tolerance::make_tolerance;
+use origami_axioms::LineContainer;
+use origami_axioms::make_tracked_line_container;
+use origami_axioms::make_round;
+use origami_axioms::make_round_plan;
+use origami_axioms::make_state;
+use origami_axioms::Origin;
+use origami_axioms::Provenance;
+use origami_axioms::polygon::make_unit_square;
+use origami_axioms::PointIndex;
+use origami_axioms::LineIndex;
+
+fn point (x: f64, y: f64) -> Vector { Vector { x, y } }
+
+fn line (x: f64, y: f64, d: f64) -> Line {
+	let length = (x * x + y * y).sqrt();
+	return Line { u: Vector { x: x / length, y: y / length }, d };
+}
+
+fn fold (axiom: u8, points: &[Vector], lines: &[Line]) -> Origin {
+	return Origin { axiom, points: points.to_vec(), lines: lines.to_vec() };
+}
+
+// a made up graph of constructions. the coordinates don't need to agree with
+// the axioms, only the origins are searched.
+// p0, p1, p2, p3 and e are initial. l is made from p1 and p2, and also
+// listed as made from x, which is where l crosses e (a cycle). k is made
+// from x and p3, and t is where l crosses k
+struct Graph {
+	points: GridVec,
+	lines: LineContainer,
+	x: Vector,
+	t: Vector,
+	l: Line,
+	k: Line,
+	unreachable: Line,
+}
+
+fn graph () -> Graph {
+	let mut points: GridVec = make_tracked_grid(Provenance::All);
+	let mut lines: LineContainer = make_tracked_line_container(Provenance::All);
+	let (p0, p1, p2, p3) = (point(0.0, 0.0), point(1.0, 0.0), point(1.0, 1.0), point(0.0, 1.0));
+	let (x, t) = (point(0.5, 0.0), point(0.25, 0.75));
+	let e = line(0.0, 1.0, 0.0);
+	let l = line(1.0, 0.0, 0.5);
+	let k = line(1.0, 1.0, 0.7);
+	let unreachable = line(1.0, 2.0, 0.3);
+	for p in [p0, p1, p2, p3].iter() { points.push(*p); }
+	lines.push(e, 0);
+	// the cycle is listed first, so it's searched first
+	lines.push_counted((l, 2, 1, 1), &vec![fold(1, &[x, p0], &[]), fold(1, &[p1, p2], &[])]);
+	points.push_counted((x, 1, 1), &vec![fold(0, &[], &[l, e])]);
+	lines.push_counted((k, 1, 1, 2), &vec![fold(1, &[x, p3], &[])]);
+	points.push_counted((t, 1, 2), &vec![fold(0, &[], &[l, k])]);
+	// only made from itself, through a point made from it
+	let y = point(0.75, 0.25);
+	lines.push_counted((unreachable, 1, 4, 2), &vec![fold(4, &[y], &[e])]);
+	points.push_counted((y, 1, 2), &vec![fold(0, &[], &[unreachable, e])]);
+	return Graph { points, lines, x, t, l, k, unreachable };
+}
+
+fn depths (steps: &Vec<Step>) -> Vec<(u8, usize)> {
+	return steps.iter().map(|step| (step.axiom, step.depth)).collect();
+}
+
+#[test]
+fn initial_geometry_has_no_steps () {
+	let g = graph();
+	let steps = sequence::point_sequence(&g.points, &g.lines, &point(1.0, 1.0)).unwrap();
+	assert_eq!(steps.len(), 0);
+	let steps = sequence::line_sequence(&g.points, &g.lines, &line(0.0, -1.0, 0.0)).unwrap();
+	assert_eq!(steps.len(), 0);
+}
+
+// reaching l through the cycle first doesn't leave x or k unreachable
+#[test]
+fn cycles_are_not_a_way_to_make_anything () {
+	let g = graph();
+	let steps = sequence::point_sequence(&g.points, &g.lines, &g.t).unwrap();
+	// l, x, k, then t
+	assert_eq!(depths(&steps), vec![(1, 1), (0, 1), (1, 2), (0, 2)]);
+	assert!(steps[0].line.unwrap().equivalent(g.l));
+	assert!(steps[0].points[0].equivalent(point(1.0, 0.0)));
+	assert!(steps[1].point.unwrap().equivalent(g.x));
+	assert!(steps[2].line.unwrap().equivalent(g.k));
+	assert!(steps[3].point.unwrap().equivalent(g.t));
+	assert!(sequence::line_sequence(&g.points, &g.lines, &g.unreachable).is_none());
+}
+
+// every search agrees, whatever was searched for first
+#[test]
+fn the_order_of_searches_does_not_matter () {
+	let g = graph();
+	let k = depths(&sequence::line_sequence(&g.points, &g.lines, &g.k).unwrap());
+	let x = depths(&sequence::point_sequence(&g.points, &g.lines, &g.x).unwrap());
+	let l = depths(&sequence::line_sequence(&g.points, &g.lines, &g.l).unwrap());
+	assert_eq!(l, vec![(1, 1)]);
+	assert_eq!(x, vec![(1, 1), (0, 1)]);
+	assert_eq!(k, vec![(1, 1), (0, 1), (1, 2)]);
+	let t = depths(&sequence::point_sequence(&g.points, &g.lines, &g.t).unwrap());
+	assert_eq!(&t[..3], &k[..]);
+}
+
+#[test]
+fn untracked_has_no_sequence () {
+	let state = make_state(make_unit_square(), Provenance::None);
+	assert!(sequence::point_sequence(&state.points, &state.lines, &point(0.0, 0.0)).is_none());
+}
+
+// the center is made in round 1 by crossing two lines which were each folded
+// in round 1
+#[test]
+fn sequences_from_a_round () {
+	let mut state = make_state(make_unit_square(), Provenance::All);
+	let mut plan = make_round_plan();
+	plan.axioms = vec![1, 2];
+	make_round(0, &plan, &mut state.points, &mut state.lines, &state.paper);
+	let center = sequence::nearest_point(&state.points, &point(0.49, 0.51)).unwrap();
+	assert!(center.0.equivalent(point(0.5, 0.5)));
+	let steps = sequence::point_sequence(&state.points, &state.lines, &center.0).unwrap();
+	assert_eq!(steps.iter().map(|step| step.depth).collect::<Vec<usize>>(), vec![1, 1, 1]);
+	assert!(steps[0].axiom != 0 && steps[1].axiom != 0 && steps[2].axiom == 0);
+	assert!(sequence::format_step(&steps[2]).starts_with("round 1: intersect"));
+}
+
+// the same search through a quadtree of points
+#[test]
+fn sequences_from_a_quadtree () {
+	let mut state = make_state(make_unit_square(), Provenance::All);
+	let mut tree: QuadTree = make_tracked_tree(Provenance::All);
+	copy_points(&state.points, &mut tree);
+	let mut plan = make_round_plan();
+	plan.axioms = vec![1, 2];
+	make_round(0, &plan, &mut state.points, &mut state.lines, &state.paper);
+	make_round(0, &plan, &mut tree, &mut state.lines.make_empty(), &state.paper);
+	let center = sequence::nearest_point(&tree, &point(0.49, 0.51)).unwrap();
+	assert!(center.0.equivalent(point(0.5, 0.5)));
+	let from_tree = sequence::point_sequence(&tree, &state.lines, &center.0).unwrap();
+	let from_grid = sequence::point_sequence(&state.points, &state.lines, &center.0).unwrap();
+	assert_eq!(from_tree.len(), 3);
+	assert_eq!(depths(&from_tree), depths(&from_grid));
+}
+
+// the parents listed in origins are the coordinates they were made from,
+// which can be anywhere within the tolerance of the point kept. they're one
+// step, not one for each listing
+#[test]
+fn parents_within_the_tolerance () {
+	let tolerance = make_tolerance(1.0e-3).unwrap();
+	let mut points: GridVec = make_tracked_grid(Provenance::All);
+	let mut lines: LineContainer = make_tracked_line_container(Provenance::All);
+	points.set_tolerance(tolerance);
+	lines.set_tolerance(tolerance);
+	let (p0, p3) = (point(0.0, 0.0), point(0.0, 1.0));
+	let (x, nearly_x, t) = (point(0.5, 0.0), point(0.50002, 0.0), point(0.25, 0.5));
+	let e = line(0.0, 1.0, 0.0);
+	let l = line(1.0, 0.0, 0.5);
+	let k = line(1.0, 0.5, 0.5);
+	let m = line(-1.0, 0.5, -0.0);
+	for p in [p0, p3].iter() { points.push(*p); }
+	lines.push(e, 0);
+	lines.push(l, 0);
+	points.push_counted((x, 1, 1), &vec![fold(0, &[], &[l, e])]);
+	lines.push_counted((k, 1, 1, 1), &vec![fold(1, &[x, p3], &[])]);
+	lines.push_counted((m, 1, 1, 1), &vec![fold(1, &[nearly_x, p0], &[])]);
+	points.push_counted((t, 1, 1), &vec![fold(0, &[], &[k, m])]);
+	let steps = sequence::point_sequence(&points, &lines, &t).unwrap();
+	assert_eq!(depths(&steps), vec![(0, 0), (1, 1), (1, 1), (0, 1)]);
+}
+
+// a line and its flip are one line, to the searches and the tables alike
+#[test]
+fn canonical_lines () {
+	let flipped = Line { u: point(-0.6, -0.8), d: -0.5 };
+	let line = sequence::canonical_line(&flipped);
+	assert_eq!((line.u.x, line.u.y, line.d), (0.6, 0.8, 0.5));
+	let line = sequence::canonical_line(&Line { u: point(-1.0, 0.0), d: 0.0 });
+	assert_eq!((line.u.x, line.u.y, line.d), (1.0, 0.0, 0.0));
+	let line = sequence::canonical_line(&Line { u: point(0.0, -1.0), d: 0.0 });
+	assert_eq!((line.u.x, line.u.y, line.d), (0.0, 1.0, 0.0));
+	let g = graph();
+	let steps = sequence::line_sequence(&g.points, &g.lines, &Line { u: point(-1.0, 0.0), d: -0.5 }).unwrap();
+	assert_eq!(depths(&steps), vec![(1, 1)]);
+}
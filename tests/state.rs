@@ -0,0 +1,185 @@
+extern crate origami_axioms;
+
+use std::fs;
+use std::io;
+use origami_axioms::math::Vector;
+use origami_axioms::math::Line;
+use origami_axioms::make_round;
+use origami_axioms::make_round_plan;
+use origami_axioms::make_state;
+use origami_axioms::Origin;
+use origami_axioms::Provenance;
+use origami_axioms::State;
+use origami_axioms::polygon::make_unit_square;
+use origami_axioms::polygon::make_regular_polygon;
+use origami_axioms::state::save_state;
+use origami_axioms::state::load_state;
+use origami_axioms::tolerance::make_tolerance;
+use origami_axioms::PointIndex;
+use origami_axioms::LineIndex;
+
+fn path (name: &str) -> String {
+	let mut path = std::env::temp_dir();
+	path.push(format!("axiom-drawings-{}-{}", std::process::id(), name));
+	return path.to_str().unwrap().to_string();
+}
+
+fn vector_bits (v: &Vector) -> (u64, u64) { (v.x.to_bits(), v.y.to_bits()) }
+
+fn line_bits (l: &Line) -> (u64, u64, u64) { (l.u.x.to_bits(), l.u.y.to_bits(), l.d.to_bits()) }
+
+fn origin_bits (origins: &Vec<Origin>) -> Vec<(u8, Vec<(u64, u64)>, Vec<(u64, u64, u64)>)> {
+	return origins.iter().map(|origin| (
+		origin.axiom,
+		origin.points.iter().map(vector_bits).collect(),
+		origin.lines.iter().map(line_bits).collect(),
+	)).collect();
+}
+
+// everything in the state, exactly, in container order
+fn contents (state: &State) -> (
+	Vec<((u64, u64), u64, u8, Vec<(u8, Vec<(u64, u64)>, Vec<(u64, u64, u64)>)>)>,
+	Vec<((u64, u64, u64), u64, u8, u8, Vec<(u8, Vec<(u64, u64)>, Vec<(u64, u64, u64)>)>)>
+) {
+	let points = state.points.flatten().iter().map(|el|
+		(vector_bits(&el.0), el.1, el.2, origin_bits(state.points.find_origins(&el.0).unwrap()))
+	).collect();
+	let lines = state.lines.flatten().iter().map(|el|
+		(line_bits(&el.0), el.1, el.2, el.3, origin_bits(state.lines.find_origins(&el.0).unwrap()))
+	).collect();
+	return (points, lines);
+}
+
+fn save (name: &str, state: &State) -> String {
+	let path = path(name);
+	save_state(&path, state.rounds, &state.paper, &state.points, &state.lines).unwrap();
+	return path;
+}
+
+fn two_rounds () -> State {
+	let mut state = make_state(make_unit_square(), Provenance::All);
+	let mut plan = make_round_plan();
+	plan.axioms = vec![1, 2, 4];
+	for round in 0..2 {
+		make_round(round, &plan, &mut state.points, &mut state.lines, &state.paper);
+	}
+	state.rounds = 2;
+	// an entry which was saved without any origins stays without
+	state.points.push_counted((Vector { x: 0.123, y: 0.456 }, 3, 2), &vec![]);
+	return state;
+}
+
+#[test]
+fn round_trip () {
+	let state = two_rounds();
+	let path = save("round-trip", &state);
+	let loaded = load_state(&path).unwrap();
+	fs::remove_file(&path).unwrap();
+	assert_eq!(loaded.rounds, 2);
+	assert_eq!(loaded.points.provenance(), Provenance::All);
+	assert_eq!(loaded.lines.provenance(), Provenance::All);
+	assert!(state.points.len() > 4 && state.lines.len() > 4);
+	assert!(state.lines.flatten().iter().any(|el| state.lines.find_origins(&el.0).unwrap().len() > 1));
+	let (points, lines) = contents(&state);
+	let (loaded_points, loaded_lines) = contents(&loaded);
+	assert_eq!(points, loaded_points);
+	assert_eq!(lines, loaded_lines);
+	assert_eq!(loaded.points.find_origins(&Vector { x: 0.123, y: 0.456 }).unwrap().len(), 0);
+}
+
+#[test]
+fn round_trip_paper () {
+	let state = make_state(make_regular_polygon(6).unwrap(), Provenance::First);
+	let path = save("paper", &state);
+	let loaded = load_state(&path).unwrap();
+	fs::remove_file(&path).unwrap();
+	assert_eq!(loaded.paper.vertices.len(), 6);
+	assert!(!loaded.paper.square);
+	assert_eq!(loaded.paper.scale, state.paper.scale);
+	assert!(loaded.paper.offset.equivalent(state.paper.offset));
+	assert_eq!(contents(&state), contents(&loaded));
+}
+
+// a run saved with a tolerance resumes with it, the same as if it never
+// stopped. a different one refiles everything
+#[test]
+fn round_trip_tolerance () {
+	let tolerance = make_tolerance(1.0e-3).unwrap();
+	let mut plan = make_round_plan();
+	plan.axioms = vec![1, 2];
+	let mut state = make_state(make_unit_square(), Provenance::First);
+	assert!(state.set_tolerance(tolerance).is_some());
+	make_round(0, &plan, &mut state.points, &mut state.lines, &state.paper);
+	state.rounds = 1;
+	let path = save("tolerance", &state);
+	let mut loaded = load_state(&path).unwrap();
+	fs::remove_file(&path).unwrap();
+	assert_eq!(loaded.points.tolerance(), tolerance);
+	assert_eq!(loaded.lines.tolerance(), tolerance);
+	assert!(loaded.set_tolerance(tolerance).is_none());
+	make_round(1, &plan, &mut state.points, &mut state.lines, &state.paper);
+	make_round(1, &plan, &mut loaded.points, &mut loaded.lines, &loaded.paper);
+	assert_eq!(contents(&state), contents(&loaded));
+	// a point 0.005 from the center is another point, until the tolerance is 0.01
+	loaded.points.push(Vector { x: 0.505, y: 0.5 });
+	let points = loaded.points.len();
+	assert_eq!(loaded.set_tolerance(make_tolerance(0.01).unwrap()), Some(tolerance));
+	assert_eq!(loaded.points.tolerance().epsilon, 0.01);
+	assert_eq!(loaded.lines.tolerance().epsilon, 0.01);
+	assert_eq!(loaded.points.len(), points - 1);
+}
+
+fn load_bytes (name: &str, bytes: &[u8]) -> io::Result<State> {
+	let path = path(name);
+	fs::write(&path, bytes).unwrap();
+	let state = load_state(&path);
+	fs::remove_file(&path).unwrap();
+	return state;
+}
+
+fn is_invalid (result: io::Result<State>) -> bool {
+	match result {
+		Ok(_) => false,
+		Err(error) => error.kind() == io::ErrorKind::InvalidData,
+	}
+}
+
+// every file cut short is invalid, none panic
+#[test]
+fn truncated () {
+	let mut state = make_state(make_unit_square(), Provenance::All);
+	let mut plan = make_round_plan();
+	plan.axioms = vec![2];
+	make_round(0, &plan, &mut state.points, &mut state.lines, &state.paper);
+	state.rounds = 1;
+	let path = save("truncated", &state);
+	let bytes = fs::read(&path).unwrap();
+	fs::remove_file(&path).unwrap();
+	for length in 0..bytes.len() {
+		assert!(is_invalid(load_bytes("truncated-part", &bytes[..length])), "cut to {} bytes", length);
+	}
+	assert!(load_bytes("truncated-whole", &bytes).is_ok());
+}
+
+// any byte changed either loads or is invalid, none panic
+#[test]
+fn corrupted () {
+	let state = make_state(make_regular_polygon(3).unwrap(), Provenance::All);
+	let path = save("corrupted", &state);
+	let bytes = fs::read(&path).unwrap();
+	fs::remove_file(&path).unwrap();
+	for i in 0..bytes.len() {
+		let mut changed = bytes.clone();
+		changed[i] ^= 0xff;
+		if let Err(error) = load_bytes("corrupted-part", &changed) {
+			assert_eq!(error.kind(), io::ErrorKind::InvalidData, "byte {} changed", i);
+		}
+	}
+	let mut magic = bytes.clone();
+	magic[0] = b'X';
+	assert!(is_invalid(load_bytes("corrupted-magic", &magic)));
+	// rounds, after the magic and version
+	let mut rounds = bytes.clone();
+	rounds[8..12].copy_from_slice(&256_u32.to_le_bytes());
+	assert!(is_invalid(load_bytes("corrupted-rounds", &rounds)));
+}
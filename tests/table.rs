@@ -0,0 +1,49 @@
+extern crate origami_axioms;
+
+use origami_axioms::math::Vector;
+use origami_axioms::math::Line;
+use origami_axioms::origami::table;
+use origami_axioms::polygon::make_unit_square;
+use origami_axioms::polygon::make_regular_polygon;
+
+fn point (x: f64, y: f64) -> Vector { Vector { x, y } }
+
+#[test]
+fn sorted_square () {
+	let square = make_unit_square();
+	let points = vec![(point(0.5, 0.25), 3, 1), (point(0.25, 0.5), 1, 2), (point(-0.0, 1.0), 2, 0)];
+	assert_eq!(table::points_csv(&points, &square), "x,y,count,round\n0,1,2,0\n0.25,0.5,1,2\n0.5,0.25,3,1\n");
+	// the flipped line is written with a positive d, its segment left to right
+	let lines = vec![(Line { u: point(0.0, -1.0), d: -0.5 }, 4, 2, 1)];
+	assert_eq!(table::lines_csv(&lines, &square), "ux,uy,d,x1,y1,x2,y2,count,axiom,round\n0,1,0.5,0,0.5,1,0.5,4,2,1\n");
+	assert!(table::is_json("points.JSON") && !table::is_json("points.csv"));
+}
+
+// the numbers of each row after the header
+fn rows (csv: &str) -> Vec<Vec<f64>> {
+	return csv.lines().skip(1)
+		.map(|row| row.split(',').map(|el| el.parse::<f64>().unwrap()).collect())
+		.collect();
+}
+
+fn close (a: &Vec<f64>, b: &[f64]) -> bool {
+	return a.len() == b.len() && (0..b.len()).all(|i| (a[i] - b[i]).abs() < 1e-12);
+}
+
+// the hexagon is drawn in the unit square, its tables are in its own units,
+// radius 1 around 0,0, like --find-point's
+#[test]
+fn paper_units () {
+	let hexagon = make_regular_polygon(6).unwrap();
+	let center = point(0.5, 3_f64.sqrt() / 4.0);
+	let points = vec![(center, 6, 1), (point(0.75, 0.0), 1, 0)];
+	let numbers = rows(&table::points_csv(&points, &hexagon));
+	assert!(close(&numbers[0], &[0.0, 0.0, 6.0, 1.0]));
+	assert!(close(&numbers[1], &[0.5, -(3_f64.sqrt()) / 2.0, 1.0, 0.0]));
+	assert!(table::points_json(&points, &hexagon).starts_with("[\n{\"x\": 0, \"y\": "));
+	// y = 0.5 in the hexagon's units, 0.25 above the middle in the unit square
+	let lines = vec![(Line { u: point(0.0, -1.0), d: -center.y - 0.25 }, 1, 1, 1)];
+	let numbers = rows(&table::lines_csv(&lines, &hexagon));
+	let x = 1.0 - 0.5 / 3_f64.sqrt();
+	assert!(close(&numbers[0], &[0.0, 1.0, 0.5, -x, 0.5, x, 0.5, 1.0, 1.0, 1.0]), "{:?}", numbers[0]);
+}
@@ -0,0 +1,45 @@
+extern crate origami_axioms;
+
+use origami_axioms::make_round;
+use origami_axioms::make_round_plan;
+use origami_axioms::make_state;
+use origami_axioms::Provenance;
+use origami_axioms::polygon::make_unit_square;
+use origami_axioms::PointIndex;
+use origami_axioms::LineIndex;
+
+// every point and line made by two rounds, in the order they're listed, with
+// their exact coordinates, counts, axioms, rounds and number of origins, and
+// how many line constructions merged
+fn explore (threads: usize) -> (Vec<(u64, u64, u64, u8, usize)>, Vec<(u64, u64, u64, u64, u8, u8, usize)>, u64) {
+	let mut state = make_state(make_unit_square(), Provenance::All);
+	let mut plan = make_round_plan();
+	plan.threads = threads;
+	for round in 0..2 {
+		make_round(round, &plan, &mut state.points, &mut state.lines, &state.paper);
+	}
+	let points = state.points.flatten().iter().map(|el| {
+		let origins = state.points.find_origins(&el.0).unwrap().len();
+		(el.0.x.to_bits(), el.0.y.to_bits(), el.1, el.2, origins)
+	}).collect();
+	let lines = state.lines.flatten().iter().map(|el| {
+		let origins = state.lines.find_origins(&el.0).unwrap().len();
+		(el.0.u.x.to_bits(), el.0.u.y.to_bits(), el.0.d.to_bits(), el.1, el.2, el.3, origins)
+	}).collect();
+	return (points, lines, state.lines.edges().merges);
+}
+
+// the threads only share out the work, each keeps the first of equivalent
+// solutions and counts the rest exactly as one thread does
+#[test]
+fn same_results_with_any_thread_count () {
+	let (serial_points, serial_lines, serial_merges) = explore(1);
+	assert!(serial_lines.len() > 4);
+	assert!(serial_merges > 0);
+	for threads in [2, 3, 8].iter() {
+		let (points, lines, merges) = explore(*threads);
+		assert_eq!(serial_points, points, "points with {} threads", threads);
+		assert_eq!(serial_lines, lines, "lines with {} threads", threads);
+		assert_eq!(serial_merges, merges, "merges with {} threads", threads);
+	}
+}
@@ -0,0 +1,145 @@
+extern crate origami_axioms;
+
+use origami_axioms::math::Vector;
+use origami_axioms::math::Line;
+use origami_axioms::GridVec;
+use origami_axioms::QuadTree;
+use origami_axioms::LineContainer;
+use origami_axioms::make_grid;
+use origami_axioms::make_tree;
+use origami_axioms::make_line_container;
+use origami_axioms::make_round;
+use origami_axioms::make_round_plan;
+use origami_axioms::make_state;
+use origami_axioms::Provenance;
+use origami_axioms::tolerance::make_tolerance;
+use origami_axioms::tolerance::default_tolerance;
+use origami_axioms::index::add_point;
+use origami_axioms::EdgeCount;
+use origami_axioms::polygon::make_unit_square;
+use origami_axioms::PointIndex;
+use origami_axioms::LineIndex;
+
+// the default is rabbit-ear's equivalent, on both sides of its edge
+#[test]
+fn default_is_equivalent () {
+	let tolerance = default_tolerance();
+	let a = Vector { x: 0.3, y: 0.7 };
+	let u = Vector { x: 0.6, y: 0.8 };
+	for i in 0..40 {
+		let offset = i as f64 * 0.05e-10;
+		let b = Vector { x: a.x + offset, y: a.y - offset };
+		assert_eq!(tolerance.within(tolerance.point_distance(&a, &b)), a.equivalent(b));
+		let line = Line { u, d: 0.4 };
+		let other = Line { u: Vector { x: -u.x - offset, y: -u.y }, d: -0.4 + offset };
+		assert_eq!(tolerance.within(tolerance.line_distance(&line, &other)), line.equivalent(other));
+	}
+	assert!(make_tolerance(0.0).is_none());
+	assert!(make_tolerance(0.5).is_none());
+	assert!(make_tolerance(std::f64::NAN).is_none());
+}
+
+// points a few cells apart at the default tolerance are one point with a
+// looser one, in a grid and a tree, wherever the cell edges fall
+#[test]
+fn loose_points () {
+	let tolerance = make_tolerance(1.0e-3).unwrap();
+	let mut grid: GridVec = make_grid();
+	let mut tree: QuadTree = make_tree();
+	grid.set_tolerance(tolerance);
+	tree.set_tolerance(tolerance);
+	for i in 1..200 {
+		let x = i as f64 / 200.0;
+		grid.push(Vector { x: x - 4.0e-4, y: 0.5 });
+		tree.push(Vector { x: x - 4.0e-4, y: 0.5 });
+		assert!(grid.increment_match(&Vector { x: x + 4.0e-4, y: 0.5 + 4.0e-4 }));
+		assert!(tree.increment_match(&Vector { x: x + 4.0e-4, y: 0.5 + 4.0e-4 }));
+	}
+	assert_eq!(grid.len(), 199);
+	assert_eq!(tree.len(), 199);
+	assert!(!grid.increment_match(&Vector { x: 0.3, y: 0.5 + 1.5e-3 }));
+	// make_empty keeps the tolerance
+	assert_eq!(grid.make_empty().tolerance(), tolerance);
+	assert_eq!(tree.make_empty().tolerance(), tolerance);
+}
+
+#[test]
+fn loose_lines () {
+	let mut lines: LineContainer = make_line_container();
+	lines.set_tolerance(make_tolerance(1.0e-4).unwrap());
+	lines.push(Line { u: Vector { x: 1.0, y: 0.0 }, d: 0.3 }, 1);
+	// across the angle wrap, and the distance
+	let angle: f64 = -5.0e-5;
+	assert!(lines.increment_match(&Line { u: Vector { x: angle.cos(), y: angle.sin() }, d: 0.3 + 5.0e-5 }));
+	assert!(lines.increment_match(&Line { u: Vector { x: -1.0, y: 5.0e-5 }, d: -0.3 }));
+	assert!(!lines.increment_match(&Line { u: Vector { x: 1.0, y: 0.0 }, d: 0.3 + 2.0e-4 }));
+	assert_eq!(lines.len(), 1);
+	assert_eq!(lines.flatten()[0].1, 3);
+}
+
+// merges more than half the tolerance away, and misses by less than twice
+// it. each construction is tallied once, by where it ended up
+#[test]
+fn edges_are_counted () {
+	let mut old: GridVec = make_grid();
+	old.set_tolerance(make_tolerance(1.0e-6).unwrap());
+	old.push(Vector { x: 0.5, y: 0.5 });
+	let mut new = old.make_empty();
+	let none = vec![];
+	add_point(&mut old, &mut new, (Vector { x: 0.5 + 1.0e-7, y: 0.5 }, 1, 1), &none);
+	add_point(&mut old, &mut new, (Vector { x: 0.5, y: 0.5 - 7.0e-7 }, 1, 1), &none);
+	// a close miss of the old point, twice. the second merges with the first
+	add_point(&mut old, &mut new, (Vector { x: 0.5 + 1.5e-6, y: 0.5 }, 1, 1), &none);
+	add_point(&mut old, &mut new, (Vector { x: 0.5 + 1.6e-6, y: 0.5 }, 1, 1), &none);
+	// nothing near
+	add_point(&mut old, &mut new, (Vector { x: 0.3, y: 0.3 }, 1, 1), &none);
+	assert_eq!((old.len(), new.len()), (1, 2));
+	let edges = new.edges();
+	assert_eq!((edges.merges, edges.close_merges, edges.close_misses), (3, 1, 1));
+	assert_eq!(old.edges(), EdgeCount::default());
+	// lookups outside a construction aren't tallied
+	assert!(old.increment_match(&Vector { x: 0.5, y: 0.5 + 9.0e-7 }));
+	assert_eq!(old.edges(), EdgeCount::default());
+	// merging adds the other's edges
+	old.merge(&mut new);
+	assert_eq!((old.edges().merges, old.edges().close_merges), (3, 1));
+}
+
+// a looser tolerance merges what's already there
+#[test]
+fn set_tolerance_merges () {
+	let mut grid: GridVec = make_grid();
+	grid.push(Vector { x: 0.5, y: 0.5 });
+	grid.push(Vector { x: 0.5 + 1.0e-5, y: 0.5 });
+	grid.push(Vector { x: 0.7, y: 0.5 });
+	assert_eq!(grid.len(), 3);
+	grid.set_tolerance(make_tolerance(1.0e-4).unwrap());
+	assert_eq!(grid.len(), 2);
+	assert_eq!(grid.flatten().iter().map(|el| el.1).max(), Some(2));
+}
+
+// two rounds with a looser tolerance are never more points or lines, and the
+// default makes what it always has
+#[test]
+fn rounds_with_a_tolerance () {
+	let mut plan = make_round_plan();
+	plan.axioms = vec![1, 2, 3];
+	plan.intersections = true;
+	let mut counts: Vec<(usize, usize)> = Vec::new();
+	for epsilon in [1.0e-10, 1.0e-3].iter() {
+		let mut state = make_state(make_unit_square(), Provenance::None);
+		state.points.set_tolerance(make_tolerance(*epsilon).unwrap());
+		state.lines.set_tolerance(make_tolerance(*epsilon).unwrap());
+		for round in 0..2 {
+			make_round(round, &plan, &mut state.points, &mut state.lines, &state.paper);
+		}
+		assert!(state.lines.edges().merges > 0);
+		counts.push((state.points.len(), state.lines.len()));
+	}
+	let mut state = make_state(make_unit_square(), Provenance::None);
+	for round in 0..2 {
+		make_round(round, &plan, &mut state.points, &mut state.lines, &state.paper);
+	}
+	assert_eq!(counts[0], (state.points.len(), state.lines.len()));
+	assert!(counts[1].0 <= counts[0].0 && counts[1].1 <= counts[0].1);
+}